@@ -0,0 +1,422 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// User-level settings stored in ~/.wt-manager/config.toml
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Ref new branches are created from (e.g. "origin/main").
+    /// Falls back to the main repository's HEAD when unset.
+    pub default_base: Option<String>,
+    pub pr: PrConfig,
+    pub prune: PruneConfig,
+    pub remove: RemoveConfig,
+    pub trash: TrashConfig,
+    pub setup: SetupConfig,
+    pub open: OpenConfig,
+    pub sync: SyncConfig,
+    /// Per-project overrides of `.wt.toml`, keyed by repository path
+    pub projects: HashMap<String, ProjectConfig>,
+}
+
+/// Project settings from `.wt.toml` committed in the repository
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub hooks: HooksConfig,
+    /// Environment variables for hooks and setup commands
+    pub env: HashMap<String, String>,
+    /// Run ecosystem auto-detection (mise, pnpm, cargo, ...); defaults to true
+    pub auto_detect: Option<bool>,
+    pub files: FilesConfig,
+    pub seed: SeedConfig,
+    pub ports: PortsConfig,
+    pub tmux: TmuxConfig,
+}
+
+/// tmux session (or window) opened for a worktree when switching to it
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct TmuxConfig {
+    pub enabled: Option<bool>,
+    pub mode: Option<TmuxMode>,
+    /// tmux layout applied to the panes (e.g. "main-vertical"); defaults to "tiled"
+    pub layout: Option<String>,
+    /// One pane per entry, with the command typed into it ("" for a plain shell)
+    pub panes: Option<Vec<String>>,
+}
+
+impl TmuxConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TmuxMode {
+    /// A session per worktree
+    #[default]
+    Session,
+    /// A window per worktree in the current session
+    Window,
+}
+
+/// Port ranges reserved per worktree, so dev servers of several worktrees don't collide
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PortsConfig {
+    pub enabled: Option<bool>,
+    /// First port of the main worktree (index 0); defaults to 3000
+    pub base: Option<u32>,
+    /// Ports per worktree; defaults to 10
+    pub range: Option<u32>,
+}
+
+impl PortsConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    pub fn base(&self) -> u32 {
+        self.base.unwrap_or(3000)
+    }
+
+    pub fn range(&self) -> u32 {
+        self.range.unwrap_or(10).max(1)
+    }
+}
+
+/// Seeding of dependency directories in new worktrees from an existing worktree
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct SeedConfig {
+    pub enabled: Option<bool>,
+    /// Glob patterns of directories to seed, relative to the worktree root
+    pub dirs: Option<Vec<String>>,
+    pub source: Option<SeedSource>,
+    pub mode: Option<SeedMode>,
+}
+
+impl SeedConfig {
+    pub fn dirs(&self) -> Vec<String> {
+        self.dirs.clone().unwrap_or_else(|| {
+            ["node_modules", "target", ".venv"].iter().map(|d| d.to_string()).collect()
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedSource {
+    /// The main worktree
+    #[default]
+    Main,
+    /// Whichever worktree touched the directory most recently
+    Recent,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedMode {
+    /// Reflink, falling back to hardlinks, then a plain copy
+    #[default]
+    Auto,
+    Reflink,
+    Hardlink,
+    Copy,
+}
+
+/// Local files (usually gitignored) brought from the main worktree into new worktrees
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct FilesConfig {
+    /// Glob patterns, relative to the repository root, of files to copy
+    pub copy: Option<Vec<String>>,
+    /// Glob patterns of files to symlink instead of copying
+    pub symlink: Option<Vec<String>>,
+    /// What to do when the file already exists in the new worktree
+    pub on_conflict: Option<ConflictPolicy>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Keep the existing file
+    #[default]
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Rename the existing file to <name>.bak, then copy
+    Backup,
+}
+
+/// Shell commands run at points of a worktree's life
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub post_create: Option<Vec<String>>,
+    pub post_switch: Option<Vec<String>>,
+    pub pre_remove: Option<Vec<String>>,
+    pub post_remove: Option<Vec<String>>,
+}
+
+impl ProjectConfig {
+    /// Apply settings from `other` on top of these
+    fn merge(mut self, other: ProjectConfig) -> Self {
+        let hooks = other.hooks;
+        self.hooks.post_create = hooks.post_create.or(self.hooks.post_create);
+        self.hooks.post_switch = hooks.post_switch.or(self.hooks.post_switch);
+        self.hooks.pre_remove = hooks.pre_remove.or(self.hooks.pre_remove);
+        self.hooks.post_remove = hooks.post_remove.or(self.hooks.post_remove);
+        self.env.extend(other.env);
+        self.auto_detect = other.auto_detect.or(self.auto_detect);
+        self.files.copy = other.files.copy.or(self.files.copy);
+        self.files.symlink = other.files.symlink.or(self.files.symlink);
+        self.files.on_conflict = other.files.on_conflict.or(self.files.on_conflict);
+        self.seed.enabled = other.seed.enabled.or(self.seed.enabled);
+        self.seed.dirs = other.seed.dirs.or(self.seed.dirs);
+        self.seed.source = other.seed.source.or(self.seed.source);
+        self.seed.mode = other.seed.mode.or(self.seed.mode);
+        self.ports.enabled = other.ports.enabled.or(self.ports.enabled);
+        self.ports.base = other.ports.base.or(self.ports.base);
+        self.ports.range = other.ports.range.or(self.ports.range);
+        self.tmux.enabled = other.tmux.enabled.or(self.tmux.enabled);
+        self.tmux.mode = other.tmux.mode.or(self.tmux.mode);
+        self.tmux.layout = other.tmux.layout.or(self.tmux.layout);
+        self.tmux.panes = other.tmux.panes.or(self.tmux.panes);
+        self
+    }
+
+    pub fn auto_detect(&self) -> bool {
+        self.auto_detect.unwrap_or(true)
+    }
+
+    /// Drop the settings of an unapproved `.wt.toml` that run commands or choose which
+    /// files of the main worktree end up in new worktrees, warning once per process
+    fn without_privileged(mut self, path: &Path) -> Self {
+        static WARNED: AtomicBool = AtomicBool::new(false);
+
+        let hooks = &self.hooks;
+        let non_empty = |list: &Option<Vec<String>>| list.as_ref().is_some_and(|items| items.iter().any(|i| !i.is_empty()));
+        let has_privileged = [&hooks.post_create, &hooks.post_switch, &hooks.pre_remove, &hooks.post_remove]
+            .into_iter()
+            .any(non_empty)
+            || non_empty(&self.tmux.panes)
+            || !self.env.is_empty()
+            || non_empty(&self.files.copy)
+            || non_empty(&self.files.symlink)
+            || self.seed.dirs.is_some()
+            || self.seed.source.is_some();
+        if has_privileged && !WARNED.swap(true, Ordering::SeqCst) {
+            eprintln!(
+                "Warning: hooks, tmux pane commands, env, [files] and seed dirs/source of {} are ignored until you review it and run `wt trust`",
+                path.display()
+            );
+        }
+
+        self.hooks = HooksConfig::default();
+        self.tmux.panes = None;
+        self.env.clear();
+        self.files = FilesConfig::default();
+        self.seed.dirs = None;
+        self.seed.source = None;
+        self
+    }
+}
+
+/// Settings for automatic setup of new and switched-to worktrees
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SetupConfig {
+    /// How many directory levels below the worktree root are scanned for subprojects
+    pub max_depth: usize,
+    /// Run setup in a detached process so switching doesn't wait for it
+    pub background: bool,
+    /// Shell for setup commands; defaults to $SHELL if POSIX-compatible, else sh
+    pub shell: Option<String>,
+    pub activation: Activation,
+    /// Only warn when setup fails instead of exiting with an error
+    pub best_effort: bool,
+    /// How many worktrees `wt new a b c` sets up at a time
+    pub jobs: usize,
+}
+
+impl Default for SetupConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            background: false,
+            shell: None,
+            activation: Activation::Auto,
+            best_effort: false,
+            jobs: 4,
+        }
+    }
+}
+
+/// How version managers (mise, nvm) are made available to setup commands
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Activation {
+    /// Source nvm.sh and run commands through `mise exec`
+    #[default]
+    Auto,
+    /// Source the shell's rc file first (~/.zshrc, ~/.bashrc, ~/.profile)
+    Rcfile,
+    /// Run commands as they are
+    None,
+}
+
+/// Settings for the worktree trash used by `wt undo`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Move removed worktrees to the trash instead of deleting them
+    pub enabled: bool,
+    /// Trash entries older than this are purged
+    pub retention_days: u64,
+    /// Ignored files to keep as well (untracked, non-ignored files are always kept)
+    pub include: Vec<String>,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: 14,
+            include: vec![".env".to_string(), ".env.*".to_string()],
+        }
+    }
+}
+
+/// Settings for `wt open`
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct OpenConfig {
+    /// Editor command, may include arguments (e.g. "code --new-window").
+    /// Falls back to $VISUAL, then the first of code, idea, zed and nvim found on PATH.
+    pub editor: Option<String>,
+    /// Open VS Code with a multi-root workspace of the worktree and the main repository
+    pub workspace: bool,
+}
+
+/// Settings for `wt sync`
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub strategy: SyncStrategy,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncStrategy {
+    /// Replay the branch's commits on top of its base
+    #[default]
+    Rebase,
+    /// Merge the base into the branch
+    Merge,
+}
+
+/// Settings for removing worktrees
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct RemoveConfig {
+    /// Also delete the branch (only if merged or pushed) when removing a worktree
+    pub delete_branch: bool,
+}
+
+/// Settings for `wt prune`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PruneConfig {
+    /// Worktrees without commits or other branch activity for this many days are considered stale
+    pub stale_days: u64,
+}
+
+impl Default for PruneConfig {
+    fn default() -> Self {
+        Self { stale_days: 30 }
+    }
+}
+
+/// Settings for `wt pr`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PrConfig {
+    /// Remote to fetch pull/merge requests from
+    pub remote: String,
+    /// Ref on the remote holding the request, `{number}` is replaced.
+    /// Detected from the remote URL when unset (GitHub or GitLab).
+    pub refspec: Option<String>,
+    /// Local branch the request is fetched into, `{number}` is replaced
+    pub branch: String,
+}
+
+impl Default for PrConfig {
+    fn default() -> Self {
+        Self {
+            remote: "origin".to_string(),
+            refspec: None,
+            branch: "pr/{number}".to_string(),
+        }
+    }
+}
+
+pub const GITHUB_PR_REFSPEC: &str = "refs/pull/{number}/head";
+pub const GITLAB_MR_REFSPEC: &str = "refs/merge-requests/{number}/head";
+
+fn get_config_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Failed to get home directory")?;
+    Ok(home.join(".wt-manager").join("config.toml"))
+}
+
+pub fn load_config() -> Result<Config> {
+    let config_path = get_config_path()?;
+
+    if !config_path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content = fs::read_to_string(&config_path)?;
+    let config: Config = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+    Ok(config)
+}
+
+pub const PROJECT_CONFIG_FILE: &str = ".wt.toml";
+
+/// The `.wt.toml` that applies to a worktree: its own, falling back to the main repository's
+pub fn find_project_config(repo_root: &Path, worktree_path: &Path) -> Option<PathBuf> {
+    [worktree_path, repo_root]
+        .into_iter()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.exists())
+}
+
+/// Load `.wt.toml` with the user's override from config.toml applied on top.
+/// Until the file is approved with `wt trust`, its hooks, tmux pane commands and env are ignored:
+/// it may come from any branch, e.g. a checked out pull request.
+pub fn load_project_config(repo_root: &Path, worktree_path: &Path) -> Result<ProjectConfig> {
+    let mut project = ProjectConfig::default();
+
+    if let Some(path) = find_project_config(repo_root, worktree_path) {
+        let content = fs::read_to_string(&path)?;
+        project = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if !crate::trust::is_approved(repo_root, &content)? {
+            project = project.without_privileged(&path);
+        }
+    }
+
+    let key = repo_root.to_string_lossy().to_string();
+    if let Some(user_override) = load_config()?.projects.remove(&key) {
+        project = project.merge(user_override);
+    }
+
+    Ok(project)
+}
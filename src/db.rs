@@ -1,8 +1,9 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, File};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Database {
@@ -14,6 +15,52 @@ pub struct ProjectInfo {
     pub path: PathBuf,
     pub name: String,
     pub last_accessed: u64,
+    /// Worktrees created by wt, keyed by branch name
+    #[serde(default)]
+    pub worktrees: HashMap<String, WorktreeRecord>,
+    /// Hash of the lockfiles each worktree was last set up with, keyed by worktree path
+    #[serde(default)]
+    pub setup_hashes: HashMap<String, String>,
+    /// Hash of the `.envrc` approved with `wt direnv trust`; set if the project is trusted
+    #[serde(default)]
+    pub direnv_approved: Option<String>,
+    /// Port block index reserved for each worktree, keyed by worktree path
+    #[serde(default)]
+    pub ports: HashMap<String, u32>,
+    /// Hash of the `.wt.toml` approved with `wt trust`; its hooks and commands run only then
+    #[serde(default)]
+    pub project_config_approved: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorktreeRecord {
+    pub path: PathBuf,
+    /// Ref the branch was created from, if wt created the branch
+    #[serde(default)]
+    pub base: Option<String>,
+    pub created_at: u64,
+}
+
+/// Hold while loading, changing and saving the database. The advisory lock on
+/// `db.json.lock` serializes updates of parallel setup threads, the background
+/// `wt __setup` process and other wt invocations; dropping the file releases it.
+fn write_lock() -> Result<File> {
+    let path = get_db_path()?.with_extension("json.lock");
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", path.display()))?;
+    Ok(file)
+}
+
+fn now_secs() -> Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
 }
 
 fn get_db_path() -> Result<PathBuf> {
@@ -35,14 +82,28 @@ pub fn load_db() -> Result<Database> {
     Ok(db)
 }
 
+/// Write the database to a temporary file and rename it over db.json, so readers never
+/// see a partially written file
 pub fn save_db(db: &Database) -> Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
     let db_path = get_db_path()?;
     let content = serde_json::to_string_pretty(db)?;
-    fs::write(&db_path, content)?;
+    let tmp_path = db_path.with_extension(format!(
+        "json.{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::write(&tmp_path, content)?;
+    if let Err(e) = fs::rename(&tmp_path, &db_path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
     Ok(())
 }
 
 pub fn save_project(repo_path: &Path) -> Result<()> {
+    let _lock = write_lock()?;
     let mut db = load_db()?;
     
     let repo_name = repo_path
@@ -52,18 +113,21 @@ pub fn save_project(repo_path: &Path) -> Result<()> {
         .to_string();
 
     let key = repo_path.to_string_lossy().to_string();
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_secs();
+    let now = now_secs()?;
 
-    db.projects.insert(
-        key,
-        ProjectInfo {
-            path: repo_path.to_path_buf(),
-            name: repo_name,
-            last_accessed: now,
-        },
-    );
+    // Keep worktree records of an already known project
+    let project = db.projects.entry(key).or_insert_with(|| ProjectInfo {
+        path: repo_path.to_path_buf(),
+        name: repo_name.clone(),
+        last_accessed: now,
+        worktrees: HashMap::new(),
+        setup_hashes: HashMap::new(),
+        direnv_approved: None,
+        ports: HashMap::new(),
+        project_config_approved: None,
+    });
+    project.name = repo_name;
+    project.last_accessed = now;
 
     save_db(&db)?;
     Ok(())
@@ -74,22 +138,249 @@ pub fn get_projects() -> Result<Vec<ProjectInfo>> {
     let mut projects: Vec<ProjectInfo> = db.projects.values().cloned().collect();
     
     // Sort by last accessed (most recent first)
-    projects.sort_by(|a, b| b.last_accessed.cmp(&a.last_accessed));
+    projects.sort_by_key(|p| std::cmp::Reverse(p.last_accessed));
     
     Ok(projects)
 }
 
 pub fn update_last_accessed(repo_path: &Path) -> Result<()> {
+    let _lock = write_lock()?;
     let mut db = load_db()?;
     let key = repo_path.to_string_lossy().to_string();
     
     if let Some(project) = db.projects.get_mut(&key) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
-        project.last_accessed = now;
+        project.last_accessed = now_secs()?;
         save_db(&db)?;
     }
     
     Ok(())
 }
+
+/// Record a worktree for a project, keeping the existing base if `base` is None
+pub fn record_worktree(repo_path: &Path, branch: &str, worktree_path: &Path, base: Option<&str>) -> Result<()> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    if let Some(project) = db.projects.get_mut(&key) {
+        let now = now_secs()?;
+        let record = project
+            .worktrees
+            .entry(branch.to_string())
+            .or_insert_with(|| WorktreeRecord {
+                path: worktree_path.to_path_buf(),
+                base: None,
+                created_at: now,
+            });
+        record.path = worktree_path.to_path_buf();
+        if let Some(base) = base {
+            record.base = Some(base.to_string());
+        }
+        save_db(&db)?;
+    }
+
+    Ok(())
+}
+
+/// Move a worktree record to a new branch name and path
+pub fn rename_worktree(repo_path: &Path, old_branch: &str, new_branch: &str, old_path: &Path, new_path: &Path) -> Result<()> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    if let Some(project) = db.projects.get_mut(&key) {
+        if let Some(mut record) = project.worktrees.remove(old_branch) {
+            record.path = new_path.to_path_buf();
+            project.worktrees.insert(new_branch.to_string(), record);
+        }
+        let (old_key, new_key) = (old_path.to_string_lossy(), new_path.to_string_lossy());
+        if let Some(hash) = project.setup_hashes.remove(old_key.as_ref()) {
+            project.setup_hashes.insert(new_key.to_string(), hash);
+        }
+        if let Some(index) = project.ports.remove(old_key.as_ref()) {
+            project.ports.insert(new_key.to_string(), index);
+        }
+        save_db(&db)?;
+    }
+
+    Ok(())
+}
+
+/// Forget a worktree record after the worktree was removed, releasing its ports
+pub fn remove_worktree_record(repo_path: &Path, branch: &str, worktree_path: &Path) -> Result<()> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    if let Some(project) = db.projects.get_mut(&key) {
+        let path_key = worktree_path.to_string_lossy();
+        let had_record = project.worktrees.remove(branch).is_some();
+        let had_hash = project.setup_hashes.remove(path_key.as_ref()).is_some();
+        let had_ports = project.ports.remove(path_key.as_ref()).is_some();
+        if had_record || had_hash || had_ports {
+            save_db(&db)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ref a worktree's branch was created from, if wt created it
+pub fn worktree_base(repo_path: &Path, branch: &str) -> Result<Option<String>> {
+    let db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    Ok(db
+        .projects
+        .get(&key)
+        .and_then(|p| p.worktrees.get(branch))
+        .and_then(|record| record.base.clone()))
+}
+
+/// Record of a worktree wt created or restored
+pub fn worktree_record(repo_path: &Path, branch: &str) -> Result<Option<WorktreeRecord>> {
+    let db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    Ok(db
+        .projects
+        .get(&key)
+        .and_then(|p| p.worktrees.get(branch))
+        .cloned())
+}
+
+/// Lockfile hash a worktree was last successfully set up with
+pub fn setup_hash(repo_path: &Path, worktree_path: &Path) -> Result<Option<String>> {
+    let db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    Ok(db
+        .projects
+        .get(&key)
+        .and_then(|p| p.setup_hashes.get(worktree_path.to_string_lossy().as_ref()))
+        .cloned())
+}
+
+/// Remember the lockfile hash of a worktree after a successful setup
+pub fn set_setup_hash(repo_path: &Path, worktree_path: &Path, hash: &str) -> Result<()> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    if let Some(project) = db.projects.get_mut(&key) {
+        project
+            .setup_hashes
+            .insert(worktree_path.to_string_lossy().to_string(), hash.to_string());
+        save_db(&db)?;
+    }
+
+    Ok(())
+}
+
+/// Hash of the approved `.envrc` of a trusted project
+pub fn direnv_approved(repo_path: &Path) -> Result<Option<String>> {
+    let db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    Ok(db.projects.get(&key).and_then(|p| p.direnv_approved.clone()))
+}
+
+/// Trust a project's `.envrc` with the given hash, or stop trusting it with None
+pub fn set_direnv_approved(repo_path: &Path, hash: Option<&str>) -> Result<()> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    if let Some(project) = db.projects.get_mut(&key) {
+        project.direnv_approved = hash.map(|h| h.to_string());
+        save_db(&db)?;
+    }
+
+    Ok(())
+}
+
+/// Hash of the approved `.wt.toml` of a project
+pub fn project_config_approved(repo_path: &Path) -> Result<Option<String>> {
+    let db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    Ok(db.projects.get(&key).and_then(|p| p.project_config_approved.clone()))
+}
+
+/// Approve a project's `.wt.toml` with the given hash, or revoke the approval with None
+pub fn set_project_config_approved(repo_path: &Path, hash: Option<&str>) -> Result<()> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    if let Some(project) = db.projects.get_mut(&key) {
+        project.project_config_approved = hash.map(|h| h.to_string());
+        save_db(&db)?;
+    }
+
+    Ok(())
+}
+
+/// Port block index reserved for a worktree
+pub fn port_index(repo_path: &Path, worktree_path: &Path) -> Result<Option<u32>> {
+    let db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    Ok(db
+        .projects
+        .get(&key)
+        .and_then(|p| p.ports.get(worktree_path.to_string_lossy().as_ref()))
+        .copied())
+}
+
+/// Lowest port block index not used by any worktree of any project.
+/// Index 0 belongs to main worktrees.
+fn next_port_index(db: &Database) -> u32 {
+    let used: Vec<u32> = db.projects.values().flat_map(|p| p.ports.values().copied()).collect();
+    (1..).find(|i| !used.contains(i)).unwrap_or_default()
+}
+
+/// Reserve the next free port block index for a worktree
+pub fn allocate_port_index(repo_path: &Path, worktree_path: &Path) -> Result<u32> {
+    let _lock = write_lock()?;
+    let mut db = load_db()?;
+    let key = repo_path.to_string_lossy().to_string();
+
+    let index = next_port_index(&db);
+
+    let project = db
+        .projects
+        .get_mut(&key)
+        .context("Project is not registered")?;
+    project.ports.insert(worktree_path.to_string_lossy().to_string(), index);
+    save_db(&db)?;
+
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_with_ports(ports: &[(&str, u32)]) -> ProjectInfo {
+        serde_json::from_value(serde_json::json!({
+            "path": "/repo",
+            "name": "repo",
+            "last_accessed": 0,
+            "ports": ports.iter().copied().collect::<HashMap<_, _>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn port_index_fills_gaps_across_projects() {
+        let mut db = Database::default();
+        assert_eq!(next_port_index(&db), 1);
+
+        db.projects.insert("/a".to_string(), project_with_ports(&[("/a/one", 1), ("/a/three", 3)]));
+        assert_eq!(next_port_index(&db), 2);
+
+        db.projects.insert("/b".to_string(), project_with_ports(&[("/b/two", 2)]));
+        assert_eq!(next_port_index(&db), 4);
+    }
+}
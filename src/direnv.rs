@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::{db, fsutil};
+
+pub const ENVRC_FILE: &str = ".envrc";
+
+/// What happened to a worktree's `.envrc` during setup
+pub enum EnvrcStatus {
+    /// Allowed automatically because the project is trusted
+    Allowed,
+    /// The project is trusted, but this `.envrc` is not the approved version
+    Changed,
+    /// The project is not trusted; `direnv allow` is up to the user
+    NotTrusted { differs_from_main: bool },
+    /// direnv is not installed
+    NoDirenv,
+}
+
+impl EnvrcStatus {
+    pub fn message(&self) -> String {
+        match self {
+            EnvrcStatus::Allowed => "✓ direnv: .envrc allowed (trusted project)".to_string(),
+            EnvrcStatus::Changed => "Warning: .envrc differs from the approved version; review it and run \
+                `direnv allow`, or `wt direnv trust` in the main worktree to approve it"
+                .to_string(),
+            EnvrcStatus::NotTrusted { differs_from_main: true } => "Found .envrc that differs from the main \
+                worktree's; review it and run `direnv allow`"
+                .to_string(),
+            EnvrcStatus::NotTrusted { differs_from_main: false } => "Found .envrc; run `direnv allow`, or \
+                `wt direnv trust` to allow it in every worktree of this project"
+                .to_string(),
+            EnvrcStatus::NoDirenv => "Found .envrc, but direnv is not installed".to_string(),
+        }
+    }
+}
+
+fn envrc_hash(dir: &Path) -> Option<String> {
+    let content = fs::read(dir.join(ENVRC_FILE)).ok()?;
+    Some(hex::encode(Sha256::digest(&content)))
+}
+
+fn direnv_allow(dir: &Path) -> Result<()> {
+    let output = Command::new("direnv")
+        .arg("allow")
+        .arg(dir.join(ENVRC_FILE))
+        .output()
+        .context("Failed to run direnv")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to allow .envrc: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Allow the `.envrc` of a worktree if the project is trusted and the file is the approved
+/// version. Returns None when there is nothing to do (no `.envrc`, or the main worktree).
+pub fn allow_if_trusted(repo_root: &Path, worktree_path: &Path) -> Result<Option<EnvrcStatus>> {
+    if worktree_path == repo_root {
+        return Ok(None);
+    }
+    let Some(hash) = envrc_hash(worktree_path) else {
+        return Ok(None);
+    };
+    if fsutil::find_in_path("direnv").is_none() {
+        return Ok(Some(EnvrcStatus::NoDirenv));
+    }
+
+    let status = match db::direnv_approved(repo_root)? {
+        Some(approved) if approved == hash => {
+            direnv_allow(worktree_path)?;
+            EnvrcStatus::Allowed
+        }
+        Some(_) => EnvrcStatus::Changed,
+        None => EnvrcStatus::NotTrusted {
+            differs_from_main: envrc_hash(repo_root).is_some_and(|main| main != hash),
+        },
+    };
+
+    Ok(Some(status))
+}
+
+/// Trust the project: approve the main worktree's `.envrc` for all its worktrees
+pub fn handle_trust(repo_root: &Path) -> Result<()> {
+    let hash = envrc_hash(repo_root)
+        .with_context(|| format!("No {} in {}", ENVRC_FILE, repo_root.display()))?;
+
+    direnv_allow(repo_root)?;
+    db::set_direnv_approved(repo_root, Some(&hash))?;
+
+    println!("✓ Trusted .envrc of {}", repo_root.display());
+    println!("  Worktrees with the same .envrc are allowed automatically");
+    Ok(())
+}
+
+pub fn handle_untrust(repo_root: &Path) -> Result<()> {
+    db::set_direnv_approved(repo_root, None)?;
+    println!("✓ .envrc of {} is no longer allowed automatically", repo_root.display());
+    Ok(())
+}
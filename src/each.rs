@@ -0,0 +1,247 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{config, git, worktree};
+
+/// Which worktrees `wt each` runs in, besides the name pattern
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum StatusFilter {
+    /// Worktrees with uncommitted changes
+    Dirty,
+    /// Worktrees without uncommitted changes
+    Clean,
+    /// Worktrees locked with `wt lock`
+    Locked,
+    /// Worktrees that are not locked
+    Unlocked,
+}
+
+pub struct EachOptions {
+    /// Glob matched against worktree names (e.g. "feature/*")
+    pub filter: Option<String>,
+    pub status: Option<StatusFilter>,
+    /// How many commands run at a time
+    pub jobs: usize,
+}
+
+/// Outcome of the command in one worktree
+struct RunResult {
+    /// None if the command was killed by a signal
+    exit_code: Option<i32>,
+    duration: Duration,
+}
+
+/// Build the command: a single argument is a shell snippet, several are a program with arguments
+fn build_command(command: &[String], repo_root: &Path, wt: &git::WorktreeInfo) -> Result<Command> {
+    let project = config::load_project_config(repo_root, &wt.path)?;
+    let ports = crate::ports::lookup(repo_root, &wt.path, &project.ports)?;
+
+    let mut cmd = match command {
+        [snippet] => {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(snippet);
+            cmd
+        }
+        [program, args @ ..] => {
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        [] => anyhow::bail!("No command given"),
+    };
+
+    cmd.current_dir(&wt.path)
+        .envs(&project.env)
+        .env("WT_REPO", repo_root)
+        .env("WT_PATH", &wt.path)
+        .env("WT_BRANCH", wt.name())
+        .envs(ports.iter().flat_map(|p| p.env()));
+    Ok(cmd)
+}
+
+fn matches_status(wt: &git::WorktreeInfo, status: StatusFilter) -> Result<bool> {
+    Ok(match status {
+        StatusFilter::Dirty => git::is_dirty(&wt.path)?,
+        StatusFilter::Clean => !git::is_dirty(&wt.path)?,
+        StatusFilter::Locked => wt.locked.is_some(),
+        StatusFilter::Unlocked => wt.locked.is_none(),
+    })
+}
+
+/// Print each line of a command's output with the worktree name in front
+fn forward_lines(reader: impl Read, prefix: &str, to_stderr: bool) {
+    for line in BufReader::new(reader).lines().map_while(|l| l.ok()) {
+        if to_stderr {
+            eprintln!("{} {}", prefix, line);
+        } else {
+            println!("{} {}", prefix, line);
+        }
+    }
+}
+
+fn run_prefixed(mut cmd: Command, prefix: &str) -> Result<RunResult> {
+    let started = Instant::now();
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to start command")?;
+
+    let stdout = child.stdout.take().context("Failed to capture stdout")?;
+    let stderr = child.stderr.take().context("Failed to capture stderr")?;
+    std::thread::scope(|scope| {
+        scope.spawn(|| forward_lines(stdout, prefix, false));
+        scope.spawn(|| forward_lines(stderr, prefix, true));
+    });
+
+    let status = child.wait()?;
+    Ok(RunResult { exit_code: status.code(), duration: started.elapsed() })
+}
+
+/// Existing worktrees matching the name pattern and status filter
+fn select_worktrees(repo_root: &Path, options: &EachOptions) -> Result<Vec<git::WorktreeInfo>> {
+    let pattern = options
+        .filter
+        .as_deref()
+        .map(glob::Pattern::new)
+        .transpose()
+        .context("Invalid worktree filter")?;
+
+    let mut worktrees = Vec::new();
+    for wt in git::list_worktrees(repo_root)? {
+        if wt.prunable || !wt.path.exists() {
+            continue;
+        }
+        if pattern.as_ref().is_some_and(|p| !p.matches(wt.name())) {
+            continue;
+        }
+        if let Some(status) = options.status {
+            if !matches_status(&wt, status)? {
+                continue;
+            }
+        }
+        worktrees.push(wt);
+    }
+    Ok(worktrees)
+}
+
+/// Run a command in every (matching) worktree and print a summary of exit codes
+pub fn handle_each(repo_root: &Path, command: &[String], options: &EachOptions) -> Result<()> {
+    let worktrees = select_worktrees(repo_root, options)?;
+    if worktrees.is_empty() {
+        println!("No matching worktrees.");
+        return Ok(());
+    }
+
+    let width = worktrees.iter().map(|wt| wt.name().len()).max().unwrap_or(0);
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<RunResult>>> = worktrees.iter().map(|_| None).collect();
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.jobs.clamp(1, worktrees.len()))
+            .map(|_| {
+                let worktrees = &worktrees;
+                let next = &next;
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        let Some(wt) = worktrees.get(index) else {
+                            break;
+                        };
+                        let prefix = format!("[{:<width$}]", wt.name(), width = width);
+                        let result = build_command(command, repo_root, wt)
+                            .and_then(|cmd| run_prefixed(cmd, &prefix));
+                        done.push((index, result));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            for (index, result) in worker.join().expect("worker thread panicked") {
+                results[index] = Some(result);
+            }
+        }
+    });
+
+    println!();
+    println!("{:<width$}  {:<6}  TIME", "WORKTREE", "EXIT", width = width.max(8));
+    let mut failed = 0;
+    for (wt, result) in worktrees.iter().zip(results.into_iter().flatten()) {
+        let (exit, time) = match result {
+            Ok(RunResult { exit_code: Some(0), duration }) => ("0".to_string(), format!("{:.1}s", duration.as_secs_f64())),
+            Ok(RunResult { exit_code, duration }) => {
+                failed += 1;
+                let exit = exit_code.map_or("signal".to_string(), |c| c.to_string());
+                (exit, format!("{:.1}s", duration.as_secs_f64()))
+            }
+            Err(e) => {
+                failed += 1;
+                ("-".to_string(), format!("{:#}", e))
+            }
+        };
+        println!("{:<width$}  {:<6}  {}", wt.name(), exit, time, width = width.max(8));
+    }
+
+    if failed > 0 {
+        anyhow::bail!("Command failed in {} of {} worktrees", failed, worktrees.len());
+    }
+    Ok(())
+}
+
+/// Run a command in one worktree with the terminal attached, returning its exit code
+pub fn handle_exec(repo_root: &Path, name: &str, command: &[String]) -> Result<i32> {
+    let wt = worktree::find_worktree(repo_root, name)?;
+    let status = build_command(command, repo_root, &wt)?
+        .status()
+        .with_context(|| format!("Failed to run `{}`", command.join(" ")))?;
+
+    // Like a shell, report death by signal as 128 + signal
+    Ok(status.code().unwrap_or_else(|| {
+        use std::os::unix::process::ExitStatusExt;
+        128 + status.signal().unwrap_or(0)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Sandbox;
+
+    fn selected(repo_root: &Path, filter: Option<&str>, status: Option<StatusFilter>) -> Vec<String> {
+        let options = EachOptions { filter: filter.map(String::from), status, jobs: 1 };
+        let mut names: Vec<String> = select_worktrees(repo_root, &options)
+            .unwrap()
+            .iter()
+            .map(|wt| wt.name().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn filters_worktrees_by_name_and_status() {
+        let sandbox = Sandbox::new("each");
+        let repo = sandbox.init_repo("repo");
+        for (branch, path) in [("feature/a", "a"), ("feature/b", "b"), ("fix/c", "c")] {
+            let path = sandbox.path(path);
+            sandbox.git(&repo, &["worktree", "add", "-q", "-b", branch, path.to_str().unwrap()]);
+        }
+        std::fs::write(sandbox.path("a/README"), "changed\n").unwrap();
+        sandbox.git(&repo, &["worktree", "lock", sandbox.path("b").to_str().unwrap()]);
+
+        assert_eq!(selected(&repo, Some("feature/*"), None), ["feature/a", "feature/b"]);
+        assert_eq!(selected(&repo, None, Some(StatusFilter::Dirty)), ["feature/a"]);
+        assert_eq!(selected(&repo, None, Some(StatusFilter::Clean)), ["feature/b", "fix/c", "main"]);
+        assert_eq!(selected(&repo, None, Some(StatusFilter::Locked)), ["feature/b"]);
+        assert_eq!(selected(&repo, Some("f*"), Some(StatusFilter::Unlocked)), ["feature/a", "fix/c"]);
+        assert!(selected(&repo, Some("release/*"), None).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Copy a file, symlink or directory tree, preserving symlinks as links
+pub fn copy_path(src: &Path, dst: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(src)
+        .with_context(|| format!("Failed to read {}", src.display()))?;
+
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(src)?;
+        std::os::unix::fs::symlink(target, dst)?;
+    } else if metadata.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(src, dst)
+            .with_context(|| format!("Failed to copy {} to {}", src.display(), dst.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Recreate a directory tree with hard links to the original files.
+/// Edits made in place to a linked file show up in both trees.
+pub fn hardlink_tree(src: &Path, dst: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(src)?;
+
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(src)?;
+        std::os::unix::fs::symlink(target, dst)?;
+    } else if metadata.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            hardlink_tree(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else {
+        fs::hard_link(src, dst)
+            .with_context(|| format!("Failed to link {} to {}", src.display(), dst.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Clone a directory tree with copy-on-write (reflink/clonefile) using the system `cp`.
+/// Fails if the filesystem doesn't support it.
+pub fn reflink_tree(src: &Path, dst: &Path) -> Result<()> {
+    let mut cmd = std::process::Command::new("cp");
+    if cfg!(target_os = "macos") {
+        cmd.arg("-c").arg("-R");
+    } else {
+        cmd.arg("-a").arg("--reflink=always");
+    }
+    let output = cmd.arg(src).arg(dst).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Reflink copy failed: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Find an executable on PATH
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
@@ -54,70 +54,125 @@ pub fn list_worktrees(repo_root: &Path) -> Result<Vec<WorktreeInfo>> {
 #[derive(Debug, Clone)]
 pub struct WorktreeInfo {
     pub path: PathBuf,
+    /// Checked out branch (empty for detached worktrees)
     pub branch: String,
+    /// Commit checked out at HEAD
+    pub head: String,
+    pub detached: bool,
     pub is_main: bool,
+    /// Lock reason if the worktree is locked (empty when no reason was given)
+    pub locked: Option<String>,
+    /// Git considers the worktree stale (e.g. its directory was deleted)
+    pub prunable: bool,
+}
+
+impl WorktreeInfo {
+    /// Name shown in selectors: the branch, or the short commit for detached worktrees
+    pub fn name(&self) -> &str {
+        if !self.detached {
+            return &self.branch;
+        }
+
+        // wt places detached worktrees at _detached/<shortsha>
+        let in_detached_dir = self
+            .path
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|n| n == "_detached");
+        match self.path.file_name().and_then(|n| n.to_str()) {
+            Some(dir_name) if in_detached_dir => dir_name,
+            _ => &self.head[..self.head.len().min(7)],
+        }
+    }
 }
 
 fn parse_worktree_list(output: &str) -> Result<Vec<WorktreeInfo>> {
     let mut worktrees = Vec::new();
-    let mut current_path: Option<PathBuf> = None;
-    let mut current_branch: Option<String> = None;
-    let mut is_main = false;
-
-    for line in output.lines() {
-        if line.starts_with("worktree ") {
-            // Save previous worktree if exists
-            if let (Some(path), Some(branch)) = (current_path.take(), current_branch.take()) {
-                worktrees.push(WorktreeInfo {
-                    path,
-                    branch,
-                    is_main,
-                });
-            }
-            
-            current_path = Some(PathBuf::from(line.trim_start_matches("worktree ")));
-            is_main = false;
-        } else if line.starts_with("branch ") {
-            let branch = line.trim_start_matches("branch ");
-            current_branch = Some(branch.trim_start_matches("refs/heads/").to_string());
-        } else if line.starts_with("bare") {
-            is_main = true;
-        } else if line.is_empty() {
-            // End of worktree entry
-            if let (Some(path), Some(branch)) = (current_path.take(), current_branch.take()) {
-                worktrees.push(WorktreeInfo {
-                    path,
-                    branch,
-                    is_main,
-                });
+
+    // Entries are separated by blank lines; the first one is the main worktree
+    for (index, entry) in output.split("\n\n").enumerate() {
+        let mut path: Option<PathBuf> = None;
+        let mut branch: Option<String> = None;
+        let mut head = String::new();
+        let mut detached = false;
+        let mut bare = false;
+        let mut locked = None;
+        let mut prunable = false;
+
+        for line in entry.lines() {
+            if let Some(p) = line.strip_prefix("worktree ") {
+                path = Some(PathBuf::from(p));
+            } else if let Some(h) = line.strip_prefix("HEAD ") {
+                head = h.to_string();
+            } else if let Some(b) = line.strip_prefix("branch ") {
+                branch = Some(b.trim_start_matches("refs/heads/").to_string());
+            } else if line == "detached" {
+                detached = true;
+            } else if line == "bare" {
+                bare = true;
+            } else if line == "locked" {
+                locked = Some(String::new());
+            } else if let Some(reason) = line.strip_prefix("locked ") {
+                locked = Some(reason.to_string());
+            } else if line.starts_with("prunable") {
+                prunable = true;
             }
         }
-    }
 
-    // Save last worktree if exists
-    if let (Some(path), Some(branch)) = (current_path, current_branch) {
+        // Bare repositories have no checkout to switch to
+        let Some(path) = path else { continue };
+        if bare || (branch.is_none() && !detached) {
+            continue;
+        }
+
         worktrees.push(WorktreeInfo {
             path,
-            branch,
-            is_main,
+            branch: branch.unwrap_or_default(),
+            head,
+            detached,
+            is_main: index == 0,
+            locked,
+            prunable,
         });
     }
 
     Ok(worktrees)
 }
 
+/// How `add_worktree` should check out the new worktree
+#[derive(Debug, Clone, Copy)]
+pub enum AddMode<'a> {
+    /// Check out an existing branch
+    Existing,
+    /// Create a new branch, optionally starting from `base` instead of HEAD
+    NewBranch { base: Option<&'a str> },
+    /// Check out a commit without a branch
+    Detach,
+}
+
 /// Add a new worktree
-pub fn add_worktree(repo_root: &Path, worktree_path: &Path, branch: &str, create_branch: bool) -> Result<()> {
+/// `branch` is the commit to check out when `mode` is `AddMode::Detach`
+pub fn add_worktree(repo_root: &Path, worktree_path: &Path, branch: &str, mode: AddMode) -> Result<()> {
     let mut cmd = Command::new("git");
     cmd.arg("worktree")
         .arg("add");
 
-    if create_branch {
-        // For new branch: git worktree add -b <branch> <path>
-        cmd.arg("-b").arg(branch).arg(worktree_path);
-    } else {
-        // For existing branch: git worktree add <path> <branch>
-        cmd.arg(worktree_path).arg(branch);
+    match mode {
+        AddMode::Existing => {
+            // For existing branch: git worktree add <path> <branch>
+            cmd.arg(worktree_path).arg(branch);
+        }
+        AddMode::NewBranch { base } => {
+            // For new branch: git worktree add -b <branch> <path> [<base>]
+            cmd.arg("-b").arg(branch).arg(worktree_path);
+            if let Some(base) = base {
+                cmd.arg(base);
+            }
+        }
+        AddMode::Detach => {
+            // For detached HEAD: git worktree add --detach <path> <commit>
+            cmd.arg("--detach").arg(worktree_path).arg(branch);
+        }
     }
 
     cmd.current_dir(repo_root);
@@ -132,20 +187,640 @@ pub fn add_worktree(repo_root: &Path, worktree_path: &Path, branch: &str, create
     Ok(())
 }
 
+/// List local branches, remote-tracking branches and tags (short names)
+pub fn list_refs(repo_root: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .arg("for-each-ref")
+        .arg("--format=%(refname:short) %(symref)")
+        .arg("refs/heads")
+        .arg("refs/remotes")
+        .arg("refs/tags")
+        .current_dir(repo_root)
+        .output()
+        .context("Failed to list refs")?;
+
+    if !output.status.success() {
+        anyhow::bail!("git for-each-ref failed");
+    }
+
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, symref) = line.split_once(' ').unwrap_or((line, ""));
+            // Skip symbolic refs like origin/HEAD
+            if name.is_empty() || !symref.is_empty() {
+                None
+            } else {
+                Some(name.to_string())
+            }
+        })
+        .collect())
+}
+
+/// Check whether `git_ref` resolves to a commit
+pub fn ref_exists(repo_root: &Path, git_ref: &str) -> bool {
+    Command::new("git")
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg(format!("{}^{{commit}}", git_ref))
+        .current_dir(repo_root)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Resolve a commit-ish to its full and abbreviated commit hashes
+pub fn resolve_commit(repo_root: &Path, commit: &str) -> Result<(String, String)> {
+    let rev = |extra: Option<&str>| -> Result<String> {
+        let mut cmd = Command::new("git");
+        cmd.arg("rev-parse").arg("--verify").arg("--quiet");
+        if let Some(extra) = extra {
+            cmd.arg(extra);
+        }
+        let output = cmd
+            .arg(format!("{}^{{commit}}", commit))
+            .current_dir(repo_root)
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!("'{}' is not a valid commit", commit);
+        }
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+
+    Ok((rev(None)?, rev(Some("--short"))?))
+}
+
+/// Get the URL configured for a remote
+pub fn remote_url(repo_root: &Path, remote: &str) -> Result<String> {
+    let output = Command::new("git")
+        .arg("remote")
+        .arg("get-url")
+        .arg(remote)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!("Remote '{}' not found", remote);
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// Fetch `src` from `remote` into the local ref `dst`, overwriting `dst` even if the
+/// remote ref was force-pushed
+pub fn fetch_ref(repo_root: &Path, remote: &str, src: &str, dst: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("fetch")
+        .arg(remote)
+        .arg(format!("+{}:{}", src, dst))
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to fetch {} from {}: {}", src, remote, stderr);
+    }
+
+    Ok(())
+}
+
+/// Get the branch checked out at `path`, or None if HEAD is detached
+pub fn current_branch(path: &Path) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .arg("symbolic-ref")
+        .arg("--quiet")
+        .arg("--short")
+        .arg("HEAD")
+        .current_dir(path)
+        .output()?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8(output.stdout)?.trim().to_string()))
+}
+
 /// Remove a worktree
-/// Returns an error if the worktree has uncommitted changes
-pub fn remove_worktree(repo_root: &Path, worktree_path: &Path) -> Result<()> {
+/// Returns an error if the worktree has uncommitted changes, unless `force` is set
+/// (which also discards untracked files)
+pub fn remove_worktree(repo_root: &Path, worktree_path: &Path, force: bool) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("worktree").arg("remove");
+    if force {
+        cmd.arg("--force");
+    }
+    let output = cmd
+        .arg(worktree_path)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to remove worktree: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Rename a local branch (also works while it is checked out in a worktree)
+pub fn rename_branch(repo_root: &Path, old: &str, new: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("branch")
+        .arg("-m")
+        .arg(old)
+        .arg(new)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to rename branch: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Move a worktree to a new location
+pub fn move_worktree(repo_root: &Path, worktree_path: &Path, new_path: &Path) -> Result<()> {
     let output = Command::new("git")
         .arg("worktree")
-        .arg("remove")
+        .arg("move")
         .arg(worktree_path)
+        .arg(new_path)
         .current_dir(repo_root)
         .output()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to remove worktree: {}", stderr);
+        anyhow::bail!("Failed to move worktree: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Lock a worktree so git refuses to move, remove or prune it
+pub fn lock_worktree(repo_root: &Path, worktree_path: &Path, reason: Option<&str>) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("worktree").arg("lock");
+    if let Some(reason) = reason {
+        cmd.arg("--reason").arg(reason);
+    }
+    let output = cmd.arg(worktree_path).current_dir(repo_root).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to lock worktree: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Unlock a locked worktree
+pub fn unlock_worktree(repo_root: &Path, worktree_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("worktree")
+        .arg("unlock")
+        .arg(worktree_path)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to unlock worktree: {}", stderr);
     }
 
     Ok(())
 }
+
+/// Run git in `dir` and return trimmed stdout, or None if git fails
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).current_dir(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok().map(|s| s.trim().to_string())
+}
+
+/// The branch merged work lands on: `default_base` from config, then origin/HEAD,
+/// then a local main/master, then the main worktree's branch
+pub fn default_branch(repo_root: &Path) -> Result<String> {
+    if let Some(base) = crate::config::load_config()?.default_base {
+        if ref_exists(repo_root, &base) {
+            return Ok(base);
+        }
+    }
+
+    if let Some(origin_head) = git_output(repo_root, &["symbolic-ref", "--quiet", "--short", "refs/remotes/origin/HEAD"]) {
+        return Ok(origin_head);
+    }
+
+    for candidate in ["main", "master"] {
+        if ref_exists(repo_root, &format!("refs/heads/{}", candidate)) {
+            return Ok(candidate.to_string());
+        }
+    }
+
+    current_branch(repo_root)?.context("Could not determine the default branch")
+}
+
+/// Resolve a ref to its commit hash
+pub fn rev_parse(repo_root: &Path, git_ref: &str) -> Option<String> {
+    git_output(repo_root, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", git_ref)])
+}
+
+/// Location of a file in the git directory, e.g. "info/exclude" (shared by all worktrees)
+pub fn git_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    git_output(dir, &["rev-parse", "--git-path", name]).map(|path| dir.join(path))
+}
+
+/// Check whether `commit` is reachable from `target`
+pub fn is_ancestor(repo_root: &Path, commit: &str, target: &str) -> bool {
+    Command::new("git")
+        .args(["merge-base", "--is-ancestor", commit, target])
+        .current_dir(repo_root)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Check whether a branch's upstream was deleted on the remote
+pub fn upstream_gone(repo_root: &Path, branch: &str) -> bool {
+    git_output(
+        repo_root,
+        &["for-each-ref", "--format=%(upstream:track)", &format!("refs/heads/{}", branch)],
+    )
+    .map(|track| track == "[gone]")
+    .unwrap_or(false)
+}
+
+/// Unix timestamp of the last commit at HEAD of `path`
+pub fn last_commit_time(path: &Path) -> Option<u64> {
+    git_output(path, &["log", "-1", "--format=%ct", "HEAD"])?.parse().ok()
+}
+
+/// Commit a branch was created at, from the oldest entry of its reflog
+pub fn branch_start(repo_root: &Path, branch: &str) -> Option<String> {
+    git_output(repo_root, &["reflog", "show", "--format=%H", &format!("refs/heads/{}", branch), "--"])?
+        .lines()
+        .last()
+        .map(|line| line.to_string())
+}
+
+/// When a branch was last created, committed to, reset or rebased, from its reflog
+pub fn branch_updated_at(repo_root: &Path, branch: &str) -> Option<u64> {
+    let entry = git_output(
+        repo_root,
+        &["reflog", "show", "-1", "--format=%gd", "--date=unix", &format!("refs/heads/{}", branch), "--"],
+    )?;
+    // refs/heads/<branch>@{<timestamp>}
+    entry.rsplit_once("@{")?.1.strip_suffix('}')?.parse().ok()
+}
+
+/// Check whether a worktree has uncommitted or untracked changes
+pub fn is_dirty(path: &Path) -> Result<bool> {
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--porcelain")
+        .current_dir(path)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git status failed: {}", stderr);
+    }
+
+    Ok(!output.stdout.is_empty())
+}
+
+/// Check whether `commit` is reachable from any remote-tracking branch
+pub fn is_pushed(repo_root: &Path, commit: &str) -> bool {
+    git_output(
+        repo_root,
+        &["for-each-ref", "--count=1", "--format=%(refname)", "--contains", commit, "refs/remotes"],
+    )
+    .is_some_and(|refs| !refs.is_empty())
+}
+
+/// Delete a local branch. Safety checks are up to the caller.
+pub fn delete_branch(repo_root: &Path, branch: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("branch")
+        .arg("-D")
+        .arg(branch)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to delete branch: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// List commits reachable from `commit` but not from any remote-tracking branch
+/// or the extra `exclude` revisions, as "<short hash> <subject>" lines
+pub fn unpushed_commits(repo_root: &Path, commit: &str, exclude: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .arg("log")
+        .arg("--format=%h %s")
+        .arg(commit)
+        .arg("--not")
+        .arg("--remotes")
+        .args(exclude)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list unpushed commits: {}", stderr);
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(|l| l.to_string())
+        .collect())
+}
+
+/// Check whether tracked files have staged or unstaged changes (untracked files are ignored)
+pub fn has_tracked_changes(path: &Path) -> Result<bool> {
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--porcelain")
+        .arg("--untracked-files=no")
+        .current_dir(path)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git status failed: {}", stderr);
+    }
+
+    Ok(!output.stdout.is_empty())
+}
+
+/// List untracked paths relative to `path`. Untracked directories are listed once with a
+/// trailing slash. With `ignored` only ignored paths are listed, otherwise only non-ignored ones.
+pub fn list_untracked(path: &Path, ignored: bool) -> Result<Vec<String>> {
+    let mut cmd = Command::new("git");
+    cmd.args(["ls-files", "--others", "--exclude-standard", "--directory", "-z"]);
+    if ignored {
+        cmd.arg("--ignored");
+    }
+    let output = cmd.current_dir(path).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git ls-files failed: {}", stderr);
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.to_string())
+        .collect())
+}
+
+/// Point `name` (a full ref like refs/wt-trash/x) at `commit`
+pub fn update_ref(repo_root: &Path, name: &str, commit: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("update-ref")
+        .arg(name)
+        .arg(commit)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to update {}: {}", name, stderr);
+    }
+
+    Ok(())
+}
+
+/// Delete a ref if it exists
+pub fn delete_ref(repo_root: &Path, name: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("update-ref")
+        .arg("-d")
+        .arg(name)
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to delete {}: {}", name, stderr);
+    }
+
+    Ok(())
+}
+
+/// Stash all changes (including untracked files) in `path`.
+/// Returns false if there was nothing to stash.
+pub fn stash_push(path: &Path, message: &str) -> Result<bool> {
+    let before = rev_parse(path, "refs/stash");
+
+    let output = Command::new("git")
+        .args(["stash", "push", "--include-untracked", "-m", message])
+        .current_dir(path)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to stash changes: {}", stderr);
+    }
+
+    Ok(rev_parse(path, "refs/stash") != before)
+}
+
+/// List stashes as (ref like "stash@{0}", subject), newest first
+pub fn stash_list(repo_root: &Path) -> Result<Vec<(String, String)>> {
+    let output = Command::new("git")
+        .args(["stash", "list", "--format=%gd%x00%s"])
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list stashes: {}", stderr);
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(|line| line.split_once('\0'))
+        .map(|(stash, subject)| (stash.to_string(), subject.to_string()))
+        .collect())
+}
+
+/// Apply a stash in `path` and drop it if it applied cleanly
+pub fn stash_pop(path: &Path, stash: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["stash", "pop", stash])
+        .current_dir(path)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to apply {}: {}", stash, stderr);
+    }
+
+    Ok(())
+}
+
+/// Fetch all remotes, pruning deleted branches
+pub fn fetch_all(repo_root: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .args(["fetch", "--all", "--prune", "--quiet"])
+        .current_dir(repo_root)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to fetch: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Upstream of a local branch (e.g. "origin/main"), if it has one
+pub fn upstream(repo_root: &Path, branch: &str) -> Option<String> {
+    git_output(
+        repo_root,
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", &format!("{}@{{upstream}}", branch)],
+    )
+}
+
+/// Number of commits in a range like "HEAD..origin/main"
+pub fn count_commits(dir: &Path, range: &str) -> usize {
+    git_output(dir, &["rev-list", "--count", range])
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Rebase or merge the branch checked out in `path` onto `base`.
+/// On conflicts the operation is aborted, leaving the worktree as it was, and an error
+/// with the conflicting files is returned.
+pub fn integrate(path: &Path, base: &str, merge: bool) -> Result<()> {
+    let (args, abort): (&[&str], &[&str]) = if merge {
+        (&["merge", "--no-edit", base], &["merge", "--abort"])
+    } else {
+        (&["rebase", base], &["rebase", "--abort"])
+    };
+
+    let output = Command::new("git").args(args).current_dir(path).output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let conflicts = git_output(path, &["diff", "--name-only", "--diff-filter=U"]).unwrap_or_default();
+    let _ = Command::new("git").args(abort).current_dir(path).output();
+
+    if conflicts.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+    }
+    anyhow::bail!("conflicts in {}", conflicts.lines().collect::<Vec<_>>().join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Sandbox;
+
+    #[test]
+    fn parses_detached_worktrees() {
+        let output = "\
+worktree /repo
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/main
+
+worktree /wt/_detached/abc1234567
+HEAD abc1234567890abc1234567890abc1234567890a
+detached
+
+worktree /elsewhere/tag-checkout
+HEAD def4567890123def4567890123def4567890123d
+detached
+";
+        let worktrees = parse_worktree_list(output).unwrap();
+        assert_eq!(worktrees.len(), 3);
+
+        assert!(worktrees[0].is_main);
+        assert_eq!(worktrees[0].name(), "main");
+
+        assert!(worktrees[1].detached);
+        assert!(worktrees[1].branch.is_empty());
+        // Worktrees created by wt are named after their directory, which may be longer than 7 chars
+        assert_eq!(worktrees[1].name(), "abc1234567");
+
+        // Other detached worktrees fall back to the short commit
+        assert_eq!(worktrees[2].name(), "def4567");
+    }
+
+    #[test]
+    fn parses_lock_reasons() {
+        let output = "\
+worktree /repo
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/main
+
+worktree /wt/feature
+HEAD 2222222222222222222222222222222222222222
+branch refs/heads/feature
+locked waiting for release
+
+worktree /wt/other
+HEAD 3333333333333333333333333333333333333333
+branch refs/heads/other
+locked
+";
+        let worktrees = parse_worktree_list(output).unwrap();
+        assert_eq!(worktrees[0].locked, None);
+        assert_eq!(worktrees[1].locked.as_deref(), Some("waiting for release"));
+        assert_eq!(worktrees[2].locked.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn parses_prunable_entries_and_skips_bare() {
+        let output = "\
+worktree /repo.git
+bare
+
+worktree /wt/gone
+HEAD 2222222222222222222222222222222222222222
+branch refs/heads/gone
+prunable gitdir file points to non-existent location
+
+worktree /wt/present
+HEAD 3333333333333333333333333333333333333333
+branch refs/heads/present
+";
+        let worktrees = parse_worktree_list(output).unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert_eq!(worktrees[0].name(), "gone");
+        assert!(worktrees[0].prunable);
+        assert!(!worktrees[1].prunable);
+    }
+
+    #[test]
+    fn integrate_aborts_on_conflicts() {
+        let sandbox = Sandbox::new("integrate");
+        let repo = &sandbox.init_repo("repo");
+        sandbox.git(repo, &["checkout", "-q", "-b", "feature"]);
+        let feature_head = sandbox.commit(repo, "README", "feature\n", "feature change");
+        sandbox.git(repo, &["checkout", "-q", "main"]);
+        sandbox.commit(repo, "README", "main\n", "main change");
+        sandbox.git(repo, &["checkout", "-q", "feature"]);
+
+        for merge in [false, true] {
+            let error = integrate(repo, "main", merge).unwrap_err();
+            assert_eq!(error.to_string(), "conflicts in README");
+            assert_eq!(sandbox.git(repo, &["rev-parse", "HEAD"]), feature_head);
+            assert!(!is_dirty(repo).unwrap());
+            assert_eq!(sandbox.git(repo, &["branch", "--show-current"]), "feature");
+        }
+    }
+}
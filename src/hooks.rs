@@ -0,0 +1,75 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::ProjectConfig;
+
+/// Points in a worktree's life where `.wt.toml` hooks run
+#[derive(Debug, Clone, Copy)]
+pub enum Hook {
+    PostCreate,
+    PostSwitch,
+    PreRemove,
+    PostRemove,
+}
+
+impl Hook {
+    fn name(&self) -> &'static str {
+        match self {
+            Hook::PostCreate => "post_create",
+            Hook::PostSwitch => "post_switch",
+            Hook::PreRemove => "pre_remove",
+            Hook::PostRemove => "post_remove",
+        }
+    }
+
+    fn commands<'a>(&self, project: &'a ProjectConfig) -> &'a [String] {
+        let commands = match self {
+            Hook::PostCreate => &project.hooks.post_create,
+            Hook::PostSwitch => &project.hooks.post_switch,
+            Hook::PreRemove => &project.hooks.pre_remove,
+            Hook::PostRemove => &project.hooks.post_remove,
+        };
+        commands.as_deref().unwrap_or_default()
+    }
+}
+
+/// Run a hook's commands in the worktree (the repository root once the worktree is gone).
+/// Stops at and returns the first failing command.
+pub fn run_hook(hook: Hook, project: &ProjectConfig, repo_root: &Path, worktree_path: &Path, name: &str) -> Result<()> {
+    let commands = hook.commands(project);
+    if commands.is_empty() {
+        return Ok(());
+    }
+
+    let dir = if worktree_path.exists() { worktree_path } else { repo_root };
+    let ports = crate::ports::lookup(repo_root, worktree_path, &project.ports)?;
+
+    for command in commands {
+        println!("Running {} hook: {}", hook.name(), command);
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(dir)
+            .envs(&project.env)
+            .env("WT_HOOK", hook.name())
+            .env("WT_REPO", repo_root)
+            .env("WT_PATH", worktree_path)
+            .env("WT_BRANCH", name)
+            .envs(ports.iter().flat_map(|p| p.env()))
+            .status()?;
+
+        if !status.success() {
+            anyhow::bail!("{} hook failed ({}): {}", hook.name(), status, command);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a hook whose failure shouldn't stop the operation, printing a warning instead
+pub fn run_hook_or_warn(hook: Hook, project: &ProjectConfig, repo_root: &Path, worktree_path: &Path, name: &str) {
+    if let Err(e) = run_hook(hook, project, repo_root, worktree_path, name) {
+        eprintln!("Warning: {}", e);
+    }
+}
@@ -31,10 +31,6 @@ impl Messages {
         }
     }
 
-    pub fn with_language(lang: Language) -> Self {
-        Self { lang }
-    }
-
     // Project selector
     pub fn select_project(&self) -> &str {
         match self.lang {
@@ -65,6 +61,13 @@ impl Messages {
         }
     }
 
+    pub fn select_base_ref(&self) -> &str {
+        match self.lang {
+            Language::English => "Select Base Ref for New Branch",
+            Language::Korean => "새 브랜치의 기준 ref 선택",
+        }
+    }
+
     pub fn switching_to_project(&self) -> &str {
         match self.lang {
             Language::English => "✓ Switching to project:",
@@ -107,6 +110,69 @@ impl Messages {
         }
     }
 
+    pub fn worktree_is_locked(&self) -> &str {
+        match self.lang {
+            Language::English => "🔒 Worktree is locked",
+            Language::Korean => "🔒 워크트리가 잠겨 있습니다",
+        }
+    }
+
+    pub fn lock_reason(&self) -> &str {
+        match self.lang {
+            Language::English => "Reason:",
+            Language::Korean => "사유:",
+        }
+    }
+
+    pub fn confirm_delete_locked(&self) -> &str {
+        match self.lang {
+            Language::English => "Unlock and delete this worktree?",
+            Language::Korean => "잠금을 해제하고 워크트리를 삭제할까요?",
+        }
+    }
+
+    pub fn confirm_delete_unpushed(&self) -> &str {
+        match self.lang {
+            Language::English => "Delete worktree with unpushed commits?",
+            Language::Korean => "푸시되지 않은 커밋이 있는 워크트리를 삭제할까요?",
+        }
+    }
+
+    pub fn unpushed_commits(&self) -> &str {
+        match self.lang {
+            Language::English => "{} commit(s) are not on any remote:",
+            Language::Korean => "리모트에 없는 커밋 {}개:",
+        }
+    }
+
+    pub fn deletion_cancelled(&self) -> &str {
+        match self.lang {
+            Language::English => "✗ Deletion cancelled",
+            Language::Korean => "✗ 삭제가 취소되었습니다",
+        }
+    }
+
+    pub fn confirm_delete_branch(&self) -> &str {
+        match self.lang {
+            Language::English => "Also delete branch '{}'?",
+            Language::Korean => "브랜치 '{}'도 삭제할까요?",
+        }
+    }
+
+    pub fn delete_branch_explanation(&self) -> &str {
+        match self.lang {
+            Language::English => "Without force, the branch is only deleted if it is merged or pushed.",
+            Language::Korean => "강제 삭제가 아니면 머지되었거나 푸시된 브랜치만 삭제됩니다.",
+        }
+    }
+
+    pub fn enter_lock_reason(&self) -> &str {
+        match self.lang {
+            Language::English => "Lock Reason (optional)",
+            Language::Korean => "잠금 사유 (선택)",
+        }
+    }
+
     pub fn failed_to_delete(&self) -> &str {
         match self.lang {
             Language::English => "✗ Failed to delete worktree:",
@@ -121,24 +187,17 @@ impl Messages {
         }
     }
 
-    pub fn force_delete_command(&self) -> &str {
-        match self.lang {
-            Language::English => "   To force delete, run:",
-            Language::Korean => "   강제 삭제하려면 다음 명령을 실행하세요:",
-        }
-    }
-
-    pub fn deps_installed(&self) -> &str {
+    pub fn stash_delete_command(&self) -> &str {
         match self.lang {
-            Language::English => "✓ Dependencies installed successfully",
-            Language::Korean => "✓ 의존성이 성공적으로 설치되었습니다",
+            Language::English => "   To stash the changes and delete, run:",
+            Language::Korean => "   변경사항을 stash하고 삭제하려면 다음 명령을 실행하세요:",
         }
     }
 
-    pub fn pnpm_install_warning(&self) -> &str {
+    pub fn force_delete_command(&self) -> &str {
         match self.lang {
-            Language::English => "Warning: Could not run pnpm install",
-            Language::Korean => "경고: pnpm install을 실행할 수 없습니다",
+            Language::English => "   To force delete, run:",
+            Language::Korean => "   강제 삭제하려면 다음 명령을 실행하세요:",
         }
     }
 
@@ -178,6 +237,41 @@ impl Messages {
         }
     }
 
+    pub fn help_ctrl_l_lock(&self) -> &str {
+        match self.lang {
+            Language::English => "Ctrl+L: Lock/Unlock",
+            Language::Korean => "Ctrl+L: 잠금/해제",
+        }
+    }
+
+    pub fn help_ctrl_o_open(&self) -> &str {
+        match self.lang {
+            Language::English => "Ctrl+O: Open in editor",
+            Language::Korean => "Ctrl+O: 에디터로 열기",
+        }
+    }
+
+    pub fn help_enter_confirm(&self) -> &str {
+        match self.lang {
+            Language::English => "Enter: Confirm",
+            Language::Korean => "Enter: 확인",
+        }
+    }
+
+    pub fn help_confirm(&self) -> &str {
+        match self.lang {
+            Language::English => "y: Yes | n/Esc: No",
+            Language::Korean => "y: 예 | n/Esc: 아니오",
+        }
+    }
+
+    pub fn help_delete_branch(&self) -> &str {
+        match self.lang {
+            Language::English => "y: Delete if safe | f: Force delete | n/Esc: Keep | Enter: Default",
+            Language::Korean => "y: 안전하면 삭제 | f: 강제 삭제 | n/Esc: 유지 | Enter: 기본값",
+        }
+    }
+
     pub fn help_cancel(&self) -> &str {
         match self.lang {
             Language::English => "Ctrl+C/Esc: Cancel",
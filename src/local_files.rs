@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::{ConflictPolicy, FilesConfig};
+use crate::fsutil;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Copy,
+    Symlink,
+}
+
+/// Paths under `root` matching `patterns`, relative to `root`.
+/// Paths inside an already matched directory are dropped.
+fn matching_paths(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    for pattern in patterns {
+        // Files may only come from inside the main worktree and go inside the new one
+        let escapes = Path::new(pattern)
+            .components()
+            .any(|c| matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_)));
+        if escapes {
+            anyhow::bail!("Invalid files pattern '{}': must be relative and stay inside the repository", pattern);
+        }
+
+        let full_pattern = root.join(pattern);
+        let full_pattern = full_pattern.to_string_lossy();
+        let matches = glob::glob(&full_pattern)
+            .with_context(|| format!("Invalid files pattern '{}'", pattern))?;
+        for path in matches.filter_map(|m| m.ok()) {
+            if let Ok(relative) = path.strip_prefix(root) {
+                paths.push(relative.to_path_buf());
+            }
+        }
+    }
+
+    paths.sort();
+    paths.dedup();
+    let mut result: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if !result.iter().any(|parent| path.starts_with(parent)) {
+            result.push(path);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Copy or symlink the configured local files from the main worktree into a new one
+pub fn bring_local_files(repo_root: &Path, worktree_path: &Path, files: &FilesConfig) -> Result<()> {
+    let policy = files.on_conflict.unwrap_or_default();
+    let mut planned: Vec<(PathBuf, Mode)> = Vec::new();
+
+    for path in matching_paths(repo_root, files.symlink.as_deref().unwrap_or_default())? {
+        planned.push((path, Mode::Symlink));
+    }
+    for path in matching_paths(repo_root, files.copy.as_deref().unwrap_or_default())? {
+        // Symlinks win when a file matches both lists
+        if !planned.iter().any(|(p, _)| path.starts_with(p)) {
+            planned.push((path, Mode::Copy));
+        }
+    }
+
+    if planned.is_empty() {
+        return Ok(());
+    }
+
+    let mut copied = 0;
+    let mut linked = 0;
+    let mut skipped = 0;
+
+    for (relative, mode) in planned {
+        let src = repo_root.join(&relative);
+        let dst = worktree_path.join(&relative);
+
+        if fs::symlink_metadata(&dst).is_ok() {
+            match policy {
+                ConflictPolicy::Skip => {
+                    println!("  Skipped {} (already exists)", relative.display());
+                    skipped += 1;
+                    continue;
+                }
+                ConflictPolicy::Overwrite => remove_path(&dst)?,
+                ConflictPolicy::Backup => {
+                    let mut backup = dst.clone().into_os_string();
+                    backup.push(".bak");
+                    fs::rename(&dst, &backup)?;
+                    println!("  Backed up existing {} to {}.bak", relative.display(), relative.display());
+                }
+            }
+        }
+
+        match mode {
+            Mode::Copy => {
+                fsutil::copy_path(&src, &dst)?;
+                println!("  Copied {}", relative.display());
+                copied += 1;
+            }
+            Mode::Symlink => {
+                if let Some(parent) = dst.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                std::os::unix::fs::symlink(&src, &dst)?;
+                println!("  Linked {}", relative.display());
+                linked += 1;
+            }
+        }
+    }
+
+    println!("✓ Local files: {} copied, {} linked, {} skipped", copied, linked, skipped);
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Sandbox;
+
+    #[test]
+    fn patterns_must_stay_inside_the_root() {
+        let sandbox = Sandbox::new("local-files");
+        let root = sandbox.path("repo");
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(root.join(".env"), "").unwrap();
+        fs::write(root.join("config/local.yml"), "").unwrap();
+        fs::write(sandbox.path("outside.env"), "").unwrap();
+
+        let patterns = [".env".to_string(), "config/*.yml".to_string()];
+        assert_eq!(
+            matching_paths(&root, &patterns).unwrap(),
+            [PathBuf::from(".env"), PathBuf::from("config/local.yml")]
+        );
+
+        for pattern in ["../outside.env", "config/../../*.env", "/etc/passwd", sandbox.path("*.env").to_str().unwrap()] {
+            let error = matching_paths(&root, &[pattern.to_string()]).unwrap_err();
+            assert!(error.to_string().contains("must be relative"), "{}", pattern);
+        }
+    }
+}
@@ -1,24 +1,289 @@
+mod config;
 mod db;
+mod direnv;
+mod each;
+mod fsutil;
 mod git;
+mod hooks;
 mod i18n;
+mod local_files;
+mod open;
+mod ports;
+mod prompt;
+mod prune;
+mod seed;
 mod tui;
 mod setup;
+mod status;
+mod sync;
+#[cfg(test)]
+#[path = "../tests/common/mod.rs"]
+mod testutil;
+mod tmux;
+mod trash;
+mod trust;
 mod worktree;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::env;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "wt")]
 #[command(about = "Advanced git worktree manager", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Branch name for worktree
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Branch name for worktree (`wt new <BRANCH>` for branches named like a subcommand)
+    #[arg(conflicts_with = "detach")]
     branch: Option<String>,
+
+    /// Ref to create a new branch from (defaults to `default_base` in config, then HEAD)
+    #[arg(long, value_name = "REF")]
+    from: Option<String>,
+
+    /// Create a detached worktree for a commit or tag
+    #[arg(long, value_name = "COMMIT", conflicts_with = "from")]
+    detach: Option<String>,
+
+    /// Run automatic setup even if lockfiles are unchanged
+    #[arg(long, global = true, conflicts_with = "no_setup")]
+    setup: bool,
+
+    /// Skip automatic setup
+    #[arg(long, global = true)]
+    no_setup: bool,
+
+    /// Run automatic setup in the background (defaults to `setup.background` in config)
+    #[arg(long, global = true)]
+    background: bool,
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Create or switch to a worktree for a branch, or create several and set them up in parallel
+    New {
+        /// Branch names for worktrees
+        #[arg(value_name = "BRANCH", required_unless_present = "batch")]
+        branches: Vec<String>,
+
+        /// Ref to create new branches from (defaults to `default_base` in config, then HEAD)
+        #[arg(long, value_name = "REF")]
+        from: Option<String>,
+
+        /// File with one `branch [base]` per line
+        #[arg(long, value_name = "FILE")]
+        batch: Option<PathBuf>,
+
+        /// How many worktrees are set up at a time (defaults to `setup.jobs` in config)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+    /// Check out a pull/merge request into a worktree
+    Pr {
+        /// Pull/merge request number
+        number: u32,
+
+        /// Remote to fetch from (defaults to `pr.remote` in config)
+        #[arg(long)]
+        remote: Option<String>,
+    },
+    /// Rename a worktree together with its branch
+    Mv {
+        /// Current branch name
+        old: String,
+        /// New branch name
+        new: String,
+    },
+    /// Open a worktree in the editor
+    Open {
+        /// Branch name of the worktree
+        branch: String,
+
+        /// Editor command (defaults to `open.editor` in config, then $VISUAL)
+        #[arg(long)]
+        editor: Option<String>,
+
+        /// Open a VS Code workspace with the worktree and the main repository
+        #[arg(long)]
+        workspace: bool,
+    },
+    /// Run a command in every worktree, e.g. `wt each -- git pull`
+    Each {
+        /// Only worktrees whose name matches this glob (e.g. "feature/*")
+        #[arg(long, value_name = "GLOB")]
+        filter: Option<String>,
+
+        /// Only worktrees in this state
+        #[arg(long, value_enum)]
+        status: Option<each::StatusFilter>,
+
+        /// How many worktrees run the command at a time
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+
+        /// Command to run; a single argument is run by `sh -c`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Run a command in a worktree without changing directory
+    Exec {
+        /// Branch name of the worktree
+        branch: String,
+
+        /// Command to run; a single argument is run by `sh -c`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Lock a worktree to protect it from removal
+    Lock {
+        /// Branch name of the worktree
+        branch: String,
+
+        /// Why the worktree is locked
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Unlock a locked worktree
+    Unlock {
+        /// Branch name of the worktree
+        branch: String,
+    },
+    /// Remove a worktree
+    Rm {
+        /// Branch name of the worktree
+        branch: String,
+
+        /// Also delete the branch if it is merged or pushed
+        #[arg(long, conflicts_with = "keep_branch")]
+        delete_branch: bool,
+
+        /// Keep the branch even if `remove.delete_branch` is set in config
+        #[arg(long)]
+        keep_branch: bool,
+
+        /// Delete the branch even if it is neither merged nor pushed
+        #[arg(long, conflicts_with = "keep_branch")]
+        force_branch: bool,
+
+        /// Remove the worktree even if it is locked
+        #[arg(long)]
+        unlock: bool,
+
+        /// Remove the worktree even if it has commits not on any remote
+        #[arg(long)]
+        allow_unpushed: bool,
+
+        /// Move the worktree to the trash so `wt undo` can restore it (defaults to `trash.enabled` in config)
+        #[arg(long, conflicts_with = "no_trash")]
+        trash: bool,
+
+        /// Delete untracked files instead of moving them to the trash
+        #[arg(long)]
+        no_trash: bool,
+
+        /// Stash uncommitted changes first; `wt new` offers to re-apply them
+        #[arg(long)]
+        stash: bool,
+    },
+    /// Restore the most recently removed worktree from the trash
+    Undo,
+    /// Manage removed worktrees kept in the trash
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommands,
+    },
+    /// Apply the hooks, tmux pane commands, env, [files] and seed dirs of the main worktree's `.wt.toml`
+    Trust,
+    /// Ignore the commands and file settings of `.wt.toml` again
+    Untrust,
+    /// Allow the project's `.envrc` automatically in new worktrees
+    Direnv {
+        #[command(subcommand)]
+        command: DirenvCommands,
+    },
+    /// Remove merged, stale and missing worktrees
+    Prune {
+        /// Days without commits or other branch activity before a worktree is stale (defaults to `prune.stale_days` in config)
+        #[arg(long)]
+        days: Option<u64>,
+
+        /// Only show what would be removed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove all candidates without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Fetch, then rebase or merge each clean worktree onto its base branch
+    Sync {
+        /// Only these branches (defaults to all worktrees)
+        branches: Vec<String>,
+
+        /// Rebase onto the base (default, see `sync.strategy` in config)
+        #[arg(long, conflicts_with = "merge")]
+        rebase: bool,
+
+        /// Merge the base into the branch
+        #[arg(long)]
+        merge: bool,
+
+        /// Don't fetch remotes first
+        #[arg(long)]
+        no_fetch: bool,
+    },
+    /// Show whether setup of each worktree is running, done or failed
+    Status,
+    /// Run automatic setup of a worktree (used for background setup)
+    #[command(name = "__setup", hide = true)]
+    Setup {
+        path: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DirenvCommands {
+    /// Approve the main worktree's .envrc for all worktrees of this project
+    Trust,
+    /// Stop allowing .envrc automatically
+    Untrust,
+}
+
+#[derive(Subcommand, Debug)]
+enum TrashCommands {
+    /// List trashed worktrees of this repository
+    List,
+    /// Restore a trashed worktree by id or name
+    Restore {
+        /// Trash entry id or worktree name
+        id: String,
+    },
+    /// Delete expired trash entries
+    Purge {
+        /// Delete all entries of this repository, not only expired ones
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {:?}", e);
+        // Scripts can tell a broken bootstrap apart from other failures
+        let code = if e.downcast_ref::<setup::SetupFailed>().is_some() {
+            setup::SETUP_FAILED_EXIT_CODE
+        } else {
+            1
+        };
+        std::process::exit(code);
+    }
+}
+
+fn run() -> Result<()> {
     // Set up Ctrl+C handler
     ctrlc::set_handler(|| {
         // Clean up terminal state if needed
@@ -34,6 +299,21 @@ fn main() -> Result<()> {
     let args = Args::parse();
     let current_dir = env::current_dir()?;
 
+    let policy = if args.setup {
+        setup::SetupPolicy::Always
+    } else if args.no_setup {
+        setup::SetupPolicy::Never
+    } else {
+        setup::SetupPolicy::Auto
+    };
+    // The background setup process itself runs in the foreground
+    let background = if matches!(args.command, Some(Commands::Setup { .. })) {
+        Some(false)
+    } else {
+        args.background.then_some(true)
+    };
+    setup::set_options(setup::SetupOptions { policy, background });
+
     // Check if we're in a git repository (use main repo root to handle worktrees)
     if let Some(repo_root) = git::find_main_repo_root(&current_dir)? {
         handle_git_repo(repo_root, args)?;
@@ -49,9 +329,121 @@ fn handle_git_repo(repo_root: PathBuf, args: Args) -> Result<()> {
     // Save this project to the database
     db::save_project(&repo_root)?;
 
-    if let Some(branch) = args.branch {
+    if let Some(command) = args.command {
+        match command {
+            Commands::New { branches, from, batch, jobs } => {
+                let mut entries: Vec<(String, Option<String>)> =
+                    branches.into_iter().map(|b| (b, from.clone())).collect();
+                if let Some(batch) = batch {
+                    entries.extend(worktree::read_batch_file(&batch)?);
+                }
+
+                match entries.as_slice() {
+                    [(branch, base)] if jobs.is_none() => {
+                        worktree::handle_worktree(&repo_root, branch, base.as_deref())?;
+                    }
+                    _ => {
+                        let jobs = match jobs {
+                            Some(jobs) => jobs,
+                            None => config::load_config()?.setup.jobs,
+                        };
+                        worktree::handle_batch(&repo_root, &entries, jobs)?;
+                    }
+                }
+            }
+            Commands::Pr { number, remote } => {
+                worktree::handle_pr(&repo_root, number, remote.as_deref())?;
+            }
+            Commands::Mv { old, new } => {
+                worktree::handle_move(&repo_root, &old, &new)?;
+            }
+            Commands::Open { branch, editor, workspace } => {
+                open::handle_open(&repo_root, &branch, editor.as_deref(), workspace)?;
+            }
+            Commands::Each { filter, status, jobs, command } => {
+                each::handle_each(&repo_root, &command, &each::EachOptions { filter, status, jobs })?;
+            }
+            Commands::Exec { branch, command } => {
+                let code = each::handle_exec(&repo_root, &branch, &command)?;
+                if code != 0 {
+                    std::process::exit(code);
+                }
+            }
+            Commands::Lock { branch, reason } => {
+                worktree::handle_lock(&repo_root, &branch, reason.as_deref())?;
+            }
+            Commands::Unlock { branch } => {
+                worktree::handle_unlock(&repo_root, &branch)?;
+            }
+            Commands::Rm { branch, delete_branch, keep_branch, force_branch, unlock, allow_unpushed, trash, no_trash, stash } => {
+                let delete_branch = if keep_branch {
+                    Some(false)
+                } else if delete_branch || force_branch {
+                    Some(true)
+                } else {
+                    None
+                };
+                let options = worktree::RemoveOptions {
+                    delete_branch,
+                    force_branch,
+                    unlock,
+                    allow_unpushed,
+                    trash: if trash {
+                        Some(true)
+                    } else {
+                        no_trash.then_some(false)
+                    },
+                    stash,
+                };
+                worktree::handle_remove(&repo_root, &branch, &options)?;
+            }
+            Commands::Undo => {
+                trash::handle_undo(&repo_root)?;
+            }
+            Commands::Trash { command } => match command {
+                TrashCommands::List => trash::handle_list(&repo_root)?,
+                TrashCommands::Restore { id } => trash::handle_restore_id(&repo_root, &id)?,
+                TrashCommands::Purge { all } => trash::handle_purge(&repo_root, all)?,
+            },
+            Commands::Trust => {
+                trust::handle_trust(&repo_root)?;
+            }
+            Commands::Untrust => {
+                trust::handle_untrust(&repo_root)?;
+            }
+            Commands::Direnv { command } => match command {
+                DirenvCommands::Trust => direnv::handle_trust(&repo_root)?,
+                DirenvCommands::Untrust => direnv::handle_untrust(&repo_root)?,
+            },
+            Commands::Prune { days, dry_run, yes } => {
+                let stale_days = match days {
+                    Some(days) => days,
+                    None => config::load_config()?.prune.stale_days,
+                };
+                prune::run_prune(&repo_root, &prune::PruneOptions { stale_days, dry_run, yes })?;
+            }
+            Commands::Sync { branches, rebase, merge, no_fetch } => {
+                let strategy = if merge {
+                    Some(config::SyncStrategy::Merge)
+                } else {
+                    rebase.then_some(config::SyncStrategy::Rebase)
+                };
+                sync::handle_sync(&repo_root, &sync::SyncOptions { branches, strategy, no_fetch })?;
+            }
+            Commands::Status => {
+                status::handle_status(&repo_root)?;
+            }
+            Commands::Setup { path } => {
+                let project = config::load_project_config(&repo_root, &path)?;
+                setup::SetupManager::run_auto_setup(&path, &project)?;
+            }
+        }
+    } else if let Some(commit) = args.detach {
+        // Detached checkout of a commit or tag
+        worktree::handle_detached(&repo_root, &commit)?;
+    } else if let Some(branch) = args.branch {
         // User specified a branch - create or switch to worktree
-        worktree::handle_worktree(&repo_root, &branch)?;
+        worktree::handle_worktree(&repo_root, &branch, args.from.as_deref())?;
     } else {
         // No branch specified - show TUI to select worktree
         tui::show_worktree_selector(&repo_root)?;
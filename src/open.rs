@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{self, OpenConfig};
+use crate::{fsutil, git, worktree};
+
+/// Editors tried in order when neither the config nor $VISUAL names one
+const FALLBACK_EDITORS: &[&str] = &["code", "idea", "zed", "nvim"];
+
+/// Editors that take over the terminal, so wt waits for them
+const TERMINAL_EDITORS: &[&str] = &["vi", "vim", "nvim", "nano", "hx", "helix", "micro", "kak", "emacs"];
+
+/// Editors that understand `.code-workspace` files
+const VSCODE_EDITORS: &[&str] = &["code", "code-insiders", "codium", "cursor"];
+
+/// Editor command from config, $VISUAL or PATH, split into program and arguments
+fn resolve_editor(config: &OpenConfig) -> Option<Vec<String>> {
+    let command = config
+        .editor
+        .clone()
+        .or_else(|| std::env::var("VISUAL").ok())
+        .filter(|editor| !editor.trim().is_empty())
+        .or_else(|| {
+            FALLBACK_EDITORS
+                .iter()
+                .find(|editor| fsutil::find_in_path(editor).is_some())
+                .map(|editor| editor.to_string())
+        })?;
+
+    Some(command.split_whitespace().map(String::from).collect())
+}
+
+fn program_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program)
+}
+
+/// Write a workspace with the worktree and the main repository next to each other.
+/// Lives in ~/.wt-manager/workspaces so it doesn't show up in `git status`.
+fn write_workspace(repo_root: &Path, worktree_path: &Path, name: &str) -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Failed to get home directory")?;
+    let dir = home.join(".wt-manager").join("workspaces");
+    fs::create_dir_all(&dir)?;
+
+    let project = repo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let path = dir.join(format!("{}_{}.code-workspace", project, name.replace('/', "-")));
+
+    let workspace = serde_json::json!({
+        "folders": [
+            { "name": name, "path": worktree_path },
+            { "name": format!("{} (main)", project), "path": repo_root },
+        ]
+    });
+    fs::write(&path, serde_json::to_string_pretty(&workspace)?)?;
+
+    Ok(path)
+}
+
+/// Launch the editor on a worktree. GUI editors are detached from wt,
+/// terminal editors get the terminal (stdout may be piped by the shell wrapper).
+pub fn open_worktree(repo_root: &Path, wt: &git::WorktreeInfo, editor: Option<&str>, workspace: bool) -> Result<()> {
+    let mut config = config::load_config()?.open;
+    if let Some(editor) = editor {
+        config.editor = Some(editor.to_string());
+    }
+
+    let command = resolve_editor(&config).context(
+        "No editor found. Set `open.editor` in ~/.wt-manager/config.toml or $VISUAL",
+    )?;
+    let (program, args) = command.split_first().context("Editor command is empty")?;
+    let name = program_name(program);
+
+    let target = if (workspace || config.workspace) && !wt.is_main {
+        if VSCODE_EDITORS.contains(&name) {
+            write_workspace(repo_root, &wt.path, wt.name())?
+        } else {
+            eprintln!("Warning: workspaces are only supported by VS Code, opening the worktree instead");
+            wt.path.clone()
+        }
+    } else {
+        wt.path.clone()
+    };
+
+    let mut cmd = Command::new(program);
+    cmd.args(args).arg(&target).current_dir(&wt.path);
+
+    if TERMINAL_EDITORS.contains(&name) {
+        if let Ok(tty) = File::options().read(true).write(true).open("/dev/tty") {
+            cmd.stdin(tty.try_clone()?).stdout(tty.try_clone()?).stderr(tty);
+        }
+        let status = cmd
+            .status()
+            .with_context(|| format!("Failed to start {}", program))?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", program, status);
+        }
+    } else {
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", program))?;
+        println!("✓ Opened {} in {}", wt.name(), name);
+    }
+
+    Ok(())
+}
+
+pub fn handle_open(repo_root: &Path, name: &str, editor: Option<&str>, workspace: bool) -> Result<()> {
+    let wt = worktree::find_worktree(repo_root, name)?;
+    open_worktree(repo_root, &wt, editor, workspace)
+}
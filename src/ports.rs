@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::config::PortsConfig;
+use crate::{db, git};
+
+pub const ENV_FILE: &str = ".env.wt";
+
+/// Ports and index reserved for one worktree
+#[derive(Debug, Clone, Copy)]
+pub struct Allocation {
+    pub index: u32,
+    pub port: u32,
+    /// Last port of the range (inclusive)
+    pub port_end: u32,
+}
+
+impl Allocation {
+    fn new(index: u32, config: &PortsConfig) -> Self {
+        let port = config.base() + index * config.range();
+        Self { index, port, port_end: port + config.range() - 1 }
+    }
+
+    /// Variables for `.env.wt`, hooks and setup commands
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("WT_INDEX", self.index.to_string()),
+            ("WT_PORT", self.port.to_string()),
+            ("WT_PORT_END", self.port_end.to_string()),
+        ]
+    }
+}
+
+/// The allocation of a worktree if it has one; the main worktree always uses index 0
+pub fn lookup(repo_root: &Path, worktree_path: &Path, config: &PortsConfig) -> Result<Option<Allocation>> {
+    if !config.enabled() {
+        return Ok(None);
+    }
+    if worktree_path == repo_root {
+        return Ok(Some(Allocation::new(0, config)));
+    }
+    Ok(db::port_index(repo_root, worktree_path)?.map(|index| Allocation::new(index, config)))
+}
+
+/// Reserve ports for a worktree (if not done yet) and write its `.env.wt`
+pub fn prepare(repo_root: &Path, worktree_path: &Path, config: &PortsConfig) -> Result<()> {
+    if !config.enabled() {
+        return Ok(());
+    }
+
+    let allocation = match lookup(repo_root, worktree_path, config)? {
+        Some(allocation) => allocation,
+        None => {
+            let allocation = Allocation::new(db::allocate_port_index(repo_root, worktree_path)?, config);
+            println!(
+                "✓ Reserved ports {}-{} (index {})",
+                allocation.port, allocation.port_end, allocation.index
+            );
+            allocation
+        }
+    };
+
+    write_env_file(worktree_path, &allocation)
+}
+
+fn write_env_file(worktree_path: &Path, allocation: &Allocation) -> Result<()> {
+    let mut content = String::from("# Generated by wt: ports reserved for this worktree\n");
+    for (key, value) in allocation.env() {
+        content.push_str(&format!("{}={}\n", key, value));
+    }
+    content.push_str(&format!("PORT={}\n", allocation.port));
+
+    let path = worktree_path.join(ENV_FILE);
+    if fs::read_to_string(&path).ok().as_deref() == Some(content.as_str()) {
+        return Ok(());
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    exclude_env_file(worktree_path)
+}
+
+/// Keep `.env.wt` out of `git status` so it never blocks removing the worktree
+fn exclude_env_file(worktree_path: &Path) -> Result<()> {
+    let Some(exclude) = git::git_path(worktree_path, "info/exclude") else {
+        return Ok(());
+    };
+
+    let content = fs::read_to_string(&exclude).unwrap_or_default();
+    let pattern = format!("/{}", ENV_FILE);
+    if content.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+
+    if let Some(parent) = exclude.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let separator = if content.is_empty() || content.ends_with('\n') { "" } else { "\n" };
+    fs::write(&exclude, format!("{}{}{}\n", content, separator, pattern))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocation_uses_a_block_per_index() {
+        let config = PortsConfig { enabled: Some(true), base: Some(4000), range: Some(5) };
+        let allocation = Allocation::new(2, &config);
+        assert_eq!((allocation.port, allocation.port_end), (4010, 4014));
+        assert_eq!(
+            allocation.env(),
+            vec![
+                ("WT_INDEX", "2".to_string()),
+                ("WT_PORT", "4010".to_string()),
+                ("WT_PORT_END", "4014".to_string()),
+            ]
+        );
+
+        let main = Allocation::new(0, &PortsConfig { range: Some(0), ..Default::default() });
+        assert_eq!((main.port, main.port_end), (3000, 3000));
+    }
+}
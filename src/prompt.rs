@@ -0,0 +1,14 @@
+use anyhow::Result;
+use std::io::{self, BufRead, Write};
+
+/// Ask a yes/no question on the terminal. Anything but "y"/"yes" answers no.
+pub fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+
+    Ok(answer == "y" || answer == "yes")
+}
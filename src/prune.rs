@@ -0,0 +1,210 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::{db, git, prompt, worktree};
+
+/// Why a worktree is a candidate for removal
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PruneReason {
+    Missing,
+    Merged,
+    UpstreamGone,
+    Stale,
+}
+
+impl PruneReason {
+    fn label(&self) -> &'static str {
+        match self {
+            PruneReason::Missing => "directory missing",
+            PruneReason::Merged => "merged",
+            PruneReason::UpstreamGone => "upstream gone",
+            PruneReason::Stale => "stale",
+        }
+    }
+}
+
+/// Why a candidate is kept anyway
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Protection {
+    Main,
+    Locked,
+    Dirty,
+    Unpushed,
+}
+
+impl Protection {
+    fn label(&self) -> &'static str {
+        match self {
+            Protection::Main => "main worktree",
+            Protection::Locked => "locked",
+            Protection::Dirty => "uncommitted changes",
+            Protection::Unpushed => "unpushed commits",
+        }
+    }
+}
+
+struct Candidate {
+    worktree: git::WorktreeInfo,
+    reasons: Vec<PruneReason>,
+    protection: Option<Protection>,
+}
+
+pub struct PruneOptions {
+    /// Worktrees without commits or other branch activity for this many days are stale
+    pub stale_days: u64,
+    /// Only show what would be removed
+    pub dry_run: bool,
+    /// Remove every candidate without asking
+    pub yes: bool,
+}
+
+fn classify(repo_root: &Path, default_branch: &str, stale_days: u64) -> Result<Vec<Candidate>> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let default_tip = git::rev_parse(repo_root, default_branch);
+    let default_local = default_branch.rsplit('/').next().unwrap_or(default_branch);
+
+    let mut candidates = Vec::new();
+    for wt in git::list_worktrees(repo_root)? {
+        let mut reasons = Vec::new();
+        let missing = wt.prunable || !wt.path.exists();
+
+        if missing {
+            reasons.push(PruneReason::Missing);
+        }
+
+        let record = db::worktree_record(repo_root, wt.name())?;
+
+        if !wt.detached && wt.branch != default_local {
+            // A branch without commits of its own has no work merged yet, even once the
+            // default branch moved past the commit it was created at
+            let has_own_commits = match record.as_ref().and_then(|r| r.base.as_ref()) {
+                Some(_) => git::branch_start(repo_root, &wt.branch).is_some_and(|start| start != wt.head),
+                // Branches wt didn't create (existing, fetched) carry their own commits
+                None => default_tip.as_deref() != Some(wt.head.as_str()),
+            };
+            if has_own_commits && git::is_ancestor(repo_root, &wt.head, default_branch) {
+                reasons.push(PruneReason::Merged);
+            }
+            if git::upstream_gone(repo_root, &wt.branch) {
+                reasons.push(PruneReason::UpstreamGone);
+            }
+        }
+
+        if !missing {
+            // A new worktree's HEAD may be an old commit it inherited, so creation and
+            // branch updates count as activity too
+            let last_activity = [
+                git::last_commit_time(&wt.path),
+                record.as_ref().map(|r| r.created_at),
+                (!wt.detached).then(|| git::branch_updated_at(repo_root, &wt.branch)).flatten(),
+            ]
+            .into_iter()
+            .flatten()
+            .max();
+            if let Some(last_activity) = last_activity {
+                if now.saturating_sub(last_activity) > stale_days * 24 * 60 * 60 {
+                    reasons.push(PruneReason::Stale);
+                }
+            }
+        }
+
+        if reasons.is_empty() {
+            continue;
+        }
+
+        let protection = if wt.is_main {
+            Some(Protection::Main)
+        } else if wt.locked.is_some() {
+            Some(Protection::Locked)
+        } else if !missing && git::is_dirty(&wt.path)? {
+            Some(Protection::Dirty)
+        } else if !worktree::unpushed_commits(repo_root, &wt)?.is_empty() {
+            Some(Protection::Unpushed)
+        } else {
+            None
+        };
+
+        candidates.push(Candidate {
+            worktree: wt,
+            reasons,
+            protection,
+        });
+    }
+
+    Ok(candidates)
+}
+
+fn print_table(candidates: &[Candidate]) {
+    let width = candidates
+        .iter()
+        .map(|c| c.worktree.name().len())
+        .max()
+        .unwrap_or(0)
+        .max("WORKTREE".len());
+
+    println!("{:<width$}  {:<36}  ACTION", "WORKTREE", "REASON", width = width);
+    for candidate in candidates {
+        let reasons: Vec<&str> = candidate.reasons.iter().map(|r| r.label()).collect();
+        let action = match candidate.protection {
+            Some(protection) => format!("keep ({})", protection.label()),
+            None => "remove".to_string(),
+        };
+        println!(
+            "{:<width$}  {:<36}  {}",
+            candidate.worktree.name(),
+            reasons.join(", "),
+            action,
+            width = width
+        );
+    }
+}
+
+/// Remove merged, orphaned, stale and missing worktrees
+pub fn run_prune(repo_root: &Path, options: &PruneOptions) -> Result<()> {
+    let default_branch = git::default_branch(repo_root)?;
+    println!("Checking worktrees against '{}'...\n", default_branch);
+
+    let candidates = classify(repo_root, &default_branch, options.stale_days)?;
+    if candidates.is_empty() {
+        println!("✓ Nothing to prune");
+        return Ok(());
+    }
+
+    print_table(&candidates);
+
+    if options.dry_run {
+        println!("\nDry run: nothing was removed");
+        return Ok(());
+    }
+
+    println!();
+    let use_trash = crate::config::load_config()?.trash.enabled;
+    let mut removed = 0;
+    for candidate in candidates.iter().filter(|c| c.protection.is_none()) {
+        let wt = &candidate.worktree;
+        if !options.yes && !prompt::confirm(&format!("Remove worktree '{}'?", wt.name()))? {
+            continue;
+        }
+
+        // Only the confirmed entry is dropped; `git worktree prune` would drop every missing one
+        let result = if wt.path.exists() {
+            worktree::remove(repo_root, wt, use_trash, false)
+        } else {
+            git::remove_worktree(repo_root, &wt.path, true)
+                .and_then(|_| db::remove_worktree_record(repo_root, wt.name(), &wt.path))
+        };
+        if let Err(e) = result {
+            eprintln!("✗ Failed to remove '{}': {}", wt.name(), e);
+            continue;
+        }
+
+        println!("✓ Removed worktree '{}'", wt.name());
+        removed += 1;
+    }
+
+    println!("\n✓ Pruned {} worktree(s)", removed);
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use crate::config::{SeedConfig, SeedMode, SeedSource};
+use crate::{fsutil, git};
+
+/// Directories matching `pattern` under `root`, relative to `root`
+fn matching_dirs(root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let full_pattern = root.join(pattern);
+    let full_pattern = full_pattern.to_string_lossy();
+    let matches = glob::glob(&full_pattern)
+        .with_context(|| format!("Invalid seed pattern '{}'", pattern))?;
+
+    Ok(matches
+        .filter_map(|m| m.ok())
+        .filter(|p| p.is_dir())
+        .filter_map(|p| p.strip_prefix(root).ok().map(|r| r.to_path_buf()))
+        .collect())
+}
+
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Worktrees that may provide seed directories
+fn source_roots(repo_root: &Path, worktree_path: &Path, source: SeedSource) -> Result<Vec<PathBuf>> {
+    match source {
+        SeedSource::Main => Ok(vec![repo_root.to_path_buf()]),
+        SeedSource::Recent => Ok(git::list_worktrees(repo_root)?
+            .into_iter()
+            .map(|wt| wt.path)
+            .filter(|p| p != worktree_path && p.exists())
+            .collect()),
+    }
+}
+
+/// The source worktree with the most recently modified directories matching `pattern`
+fn pick_source(roots: &[PathBuf], pattern: &str) -> Result<Option<(PathBuf, Vec<PathBuf>)>> {
+    let mut best: Option<(SystemTime, PathBuf, Vec<PathBuf>)> = None;
+
+    for root in roots {
+        let dirs = matching_dirs(root, pattern)?;
+        let Some(newest) = dirs.iter().map(|d| modified(&root.join(d))).max() else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|(time, _, _)| newest > *time) {
+            best = Some((newest, root.clone(), dirs));
+        }
+    }
+
+    Ok(best.map(|(_, root, dirs)| (root, dirs)))
+}
+
+/// Clone a directory with the cheapest method the filesystem supports.
+/// Returns the method used.
+fn clone_dir(src: &Path, dst: &Path, mode: SeedMode) -> Result<&'static str> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let attempts: &[SeedMode] = match mode {
+        SeedMode::Auto => &[SeedMode::Reflink, SeedMode::Hardlink, SeedMode::Copy],
+        SeedMode::Reflink => &[SeedMode::Reflink],
+        SeedMode::Hardlink => &[SeedMode::Hardlink],
+        SeedMode::Copy => &[SeedMode::Copy],
+    };
+
+    let mut last_error = None;
+    for attempt in attempts {
+        let result = match attempt {
+            SeedMode::Reflink => fsutil::reflink_tree(src, dst).map(|_| "reflink"),
+            SeedMode::Hardlink => fsutil::hardlink_tree(src, dst).map(|_| "hardlink"),
+            _ => fsutil::copy_path(src, dst).map(|_| "copy"),
+        };
+        match result {
+            Ok(method) => return Ok(method),
+            Err(e) => {
+                // Clean up a partial clone before the next attempt
+                let _ = fs::remove_dir_all(dst);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No seed method available")))
+}
+
+/// Seed dependency directories (node_modules, target, ...) of a new worktree from an
+/// existing one, so the package manager only has to catch up on differences
+pub fn seed_worktree(repo_root: &Path, worktree_path: &Path, seed: &SeedConfig) -> Result<()> {
+    if !seed.enabled.unwrap_or(false) {
+        return Ok(());
+    }
+
+    let roots = source_roots(repo_root, worktree_path, seed.source.unwrap_or_default())?;
+    let mode = seed.mode.unwrap_or_default();
+
+    for pattern in seed.dirs() {
+        let Some((root, dirs)) = pick_source(&roots, &pattern)? else {
+            continue;
+        };
+
+        for relative in dirs {
+            let dst = worktree_path.join(&relative);
+            if dst.exists() {
+                continue;
+            }
+
+            let started = Instant::now();
+            match clone_dir(&root.join(&relative), &dst, mode) {
+                Ok(method) => println!(
+                    "✓ Seeded {} from {} ({}, {:.1}s)",
+                    relative.display(),
+                    root.display(),
+                    method,
+                    started.elapsed().as_secs_f64()
+                ),
+                Err(e) => eprintln!("Warning: Could not seed {}: {}", relative.display(), e),
+            }
+        }
+    }
+
+    Ok(())
+}
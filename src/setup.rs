@@ -1,72 +1,1112 @@
-use anyhow::Result;
-use std::path::Path;
-use std::process::Command;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::config::{Activation, ProjectConfig, SetupConfig};
+use crate::status::{self, SetupState, SetupStatus};
+use crate::{db, direnv, fsutil, git};
+
+/// Exit code of wt when automatic setup fails
+pub const SETUP_FAILED_EXIT_CODE: i32 = 3;
+
+/// Outcome of one setup script
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    pub dir: PathBuf,
+    pub command: String,
+    pub duration: Duration,
+    /// None if the command couldn't start or was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+}
+
+/// Outcome of an automatic setup run; empty if nothing had to run
+#[derive(Debug, Clone, Default)]
+pub struct SetupReport {
+    pub steps: Vec<StepReport>,
+    pub log: PathBuf,
+}
+
+impl SetupReport {
+    pub fn succeeded(&self) -> bool {
+        self.steps.iter().all(|s| s.success)
+    }
+
+    pub fn failed_step(&self) -> Option<&StepReport> {
+        self.steps.iter().find(|s| !s.success)
+    }
+
+    pub fn print_summary(&self, worktree_path: &Path) {
+        eprintln!("Setup summary:");
+        for step in &self.steps {
+            let relative = step.dir.strip_prefix(worktree_path).unwrap_or(&step.dir);
+            let location = if relative.as_os_str().is_empty() { Path::new(".") } else { relative };
+            let outcome = match (step.success, step.exit_code) {
+                (true, _) => "ok".to_string(),
+                (false, Some(code)) => format!("exit {}", code),
+                (false, None) => "not run or killed".to_string(),
+            };
+            eprintln!(
+                "  {} {:<20} {:<30} {:>6.1}s  {}",
+                if step.success { "✓" } else { "✗" },
+                location.display(),
+                step.command,
+                step.duration.as_secs_f64(),
+                outcome
+            );
+        }
+    }
+}
+
+/// Setup failed and `setup.best_effort` is off
+#[derive(Debug)]
+pub struct SetupFailed(pub SetupReport);
+
+impl std::fmt::Display for SetupFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.failed_step() {
+            Some(step) => write!(
+                f,
+                "Setup failed: `{}` in {}, see {}",
+                step.command,
+                step.dir.display(),
+                self.0.log.display()
+            ),
+            None => write!(f, "Setup failed, see {}", self.0.log.display()),
+        }
+    }
+}
+
+impl std::error::Error for SetupFailed {}
+
+/// When automatic setup runs, chosen with `--setup`/`--no-setup`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SetupPolicy {
+    /// Only when lockfiles changed since the last successful setup
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How automatic setup runs in this invocation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SetupOptions {
+    pub policy: SetupPolicy,
+    /// Continue setup in a detached process; None uses `setup.background` from config
+    pub background: Option<bool>,
+}
+
+static OPTIONS: OnceLock<SetupOptions> = OnceLock::new();
+
+/// Set the setup options for this invocation
+pub fn set_options(options: SetupOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+fn options() -> SetupOptions {
+    OPTIONS.get().copied().unwrap_or_default()
+}
+
+/// Recognizes an ecosystem by its marker files and knows how to bootstrap it
+pub trait Detector {
+    fn name(&self) -> &'static str;
+
+    /// Detectors in the same group are alternatives; only the first match per directory runs.
+    /// A group matched in a directory also covers its subdirectories (e.g. workspace members).
+    fn group(&self) -> &'static str;
+
+    /// Files whose presence in a directory enables this detector
+    fn markers(&self) -> &'static [&'static str];
+
+    /// Command that sets up a directory containing one of the markers
+    fn command(&self, dir: &Path) -> String;
+}
+
+/// A detector that always runs the same command
+struct MarkerDetector {
+    name: &'static str,
+    group: &'static str,
+    markers: &'static [&'static str],
+    command: &'static str,
+}
+
+impl Detector for MarkerDetector {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn group(&self) -> &'static str {
+        self.group
+    }
+
+    fn markers(&self) -> &'static [&'static str] {
+        self.markers
+    }
+
+    fn command(&self, _dir: &Path) -> String {
+        self.command.to_string()
+    }
+}
+
+/// pip installs into a project-local virtualenv
+struct PipDetector;
+
+impl Detector for PipDetector {
+    fn name(&self) -> &'static str {
+        "pip"
+    }
+
+    fn group(&self) -> &'static str {
+        "python"
+    }
+
+    fn markers(&self) -> &'static [&'static str] {
+        &["requirements.txt"]
+    }
+
+    fn command(&self, _dir: &Path) -> String {
+        "python3 -m venv .venv && .venv/bin/pip install -r requirements.txt".to_string()
+    }
+}
+
+/// Gradle prefers the project's wrapper script
+struct GradleDetector;
+
+impl Detector for GradleDetector {
+    fn name(&self) -> &'static str {
+        "gradle"
+    }
+
+    fn group(&self) -> &'static str {
+        "jvm"
+    }
+
+    fn markers(&self) -> &'static [&'static str] {
+        &["settings.gradle", "settings.gradle.kts", "build.gradle", "build.gradle.kts"]
+    }
+
+    fn command(&self, dir: &Path) -> String {
+        if dir.join("gradlew").exists() {
+            "./gradlew dependencies".to_string()
+        } else {
+            "gradle dependencies".to_string()
+        }
+    }
+}
+
+/// All known detectors. Environment setup (version managers) comes first,
+/// then package managers in order of preference within each group.
+pub fn detectors() -> Vec<Box<dyn Detector>> {
+    vec![
+        // Environment
+        Box::new(MarkerDetector { name: "mise", group: "env", markers: &["mise.toml", ".mise.toml"], command: "mise install" }),
+        Box::new(MarkerDetector { name: "nvm", group: "env", markers: &[".nvmrc"], command: "nvm use" }),
+        // Node.js
+        Box::new(MarkerDetector { name: "pnpm", group: "node", markers: &["pnpm-lock.yaml"], command: "pnpm install" }),
+        Box::new(MarkerDetector { name: "yarn", group: "node", markers: &["yarn.lock"], command: "yarn install" }),
+        Box::new(MarkerDetector { name: "npm", group: "node", markers: &["package-lock.json"], command: "npm install" }),
+        // Python
+        Box::new(MarkerDetector { name: "uv", group: "python", markers: &["uv.lock"], command: "uv sync" }),
+        Box::new(MarkerDetector { name: "poetry", group: "python", markers: &["poetry.lock"], command: "poetry install" }),
+        Box::new(PipDetector),
+        // Others
+        Box::new(MarkerDetector { name: "cargo", group: "rust", markers: &["Cargo.toml"], command: "cargo fetch" }),
+        Box::new(MarkerDetector { name: "go", group: "go", markers: &["go.mod"], command: "go mod download" }),
+        Box::new(MarkerDetector { name: "bundler", group: "ruby", markers: &["Gemfile"], command: "bundle install" }),
+        Box::new(MarkerDetector { name: "composer", group: "php", markers: &["composer.json"], command: "composer install" }),
+        Box::new(GradleDetector),
+    ]
+}
+
+/// A setup command to run in a directory
+#[derive(Debug, Clone)]
+pub struct SetupStep {
+    pub dir: PathBuf,
+    pub detector: &'static str,
+    pub command: String,
+}
+
+/// Files besides the detector markers whose changes call for setup to run again
+const EXTRA_INPUTS: &[&str] = &[
+    "Cargo.lock",
+    "go.sum",
+    "Gemfile.lock",
+    "composer.lock",
+    ".tool-versions",
+    ".node-version",
+    ".python-version",
+];
+
+/// Hash of the steps and the lockfiles/tool version files they depend on
+pub fn inputs_hash(root: &Path, steps: &[SetupStep], detectors: &[Box<dyn Detector>]) -> String {
+    let mut hasher = Sha256::new();
+
+    for step in steps {
+        let relative = step.dir.strip_prefix(root).unwrap_or(&step.dir);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(step.command.as_bytes());
+
+        let markers = detectors
+            .iter()
+            .find(|d| d.name() == step.detector)
+            .map(|d| d.markers())
+            .unwrap_or_default();
+        for file in markers.iter().chain(EXTRA_INPUTS) {
+            if let Ok(content) = fs::read(step.dir.join(file)) {
+                hasher.update(file.as_bytes());
+                hasher.update(&content);
+            }
+        }
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+/// Directories never scanned for nested subprojects
+const SKIP_DIRS: &[&str] = &["node_modules", "target", "vendor", "build", "dist", "venv"];
+
+/// Find setup steps in `root` and its subdirectories up to `max_depth` levels deep
+pub fn detect_steps(root: &Path, detectors: &[Box<dyn Detector>], max_depth: usize) -> Vec<SetupStep> {
+    let mut steps = Vec::new();
+    detect_in_dir(root, detectors, max_depth, &[], &mut steps);
+    steps
+}
+
+fn detect_in_dir(
+    dir: &Path,
+    detectors: &[Box<dyn Detector>],
+    depth_left: usize,
+    covered_groups: &[&'static str],
+    steps: &mut Vec<SetupStep>,
+) {
+    let mut groups = covered_groups.to_vec();
+
+    for detector in detectors {
+        if groups.contains(&detector.group()) {
+            continue;
+        }
+        if detector.markers().iter().any(|m| dir.join(m).exists()) {
+            groups.push(detector.group());
+            steps.push(SetupStep {
+                dir: dir.to_path_buf(),
+                detector: detector.name(),
+                command: detector.command(dir),
+            });
+        }
+    }
+
+    if depth_left == 0 {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_ref())
+        })
+        .map(|e| e.path())
+        .collect();
+    subdirs.sort();
+
+    for subdir in subdirs {
+        detect_in_dir(&subdir, detectors, depth_left - 1, &groups, steps);
+    }
+}
 
 pub struct SetupManager;
 
+/// Where a setup run shows its progress
+enum Reporter {
+    Terminal,
+    /// One of several setups run by `SetupManager::run_jobs`
+    Job { index: usize, events: mpsc::Sender<(usize, JobEvent)> },
+}
+
+enum JobEvent {
+    Step(String),
+    Line(String),
+    Finished(Result<SetupReport>),
+}
+
+impl Reporter {
+    fn info(&self, message: &str) {
+        if let Reporter::Terminal = self {
+            println!("{}", message);
+        }
+    }
+
+    fn send(&self, event: JobEvent) {
+        if let Reporter::Job { index, events } = self {
+            let _ = events.send((*index, event));
+        }
+    }
+}
+
+/// A worktree to set up with `SetupManager::run_jobs`
+pub struct SetupJob {
+    pub name: String,
+    pub path: PathBuf,
+}
+
 impl SetupManager {
-    /// Run automatic setup based on project files (mise, nvm, pnpm, yarn, npm)
-    pub fn run_auto_setup(worktree_path: &Path) -> Result<()> {
-        let mut commands = Vec::new();
-        let mut shell_cmd = String::new();
+    /// Run automatic setup for every ecosystem detected in the worktree and its subprojects
+    pub fn run_auto_setup(worktree_path: &Path, project: &ProjectConfig) -> Result<SetupReport> {
+        Self::run(worktree_path, project, &Reporter::Terminal)
+    }
+
+    fn run(worktree_path: &Path, project: &ProjectConfig, reporter: &Reporter) -> Result<SetupReport> {
+        let options = options();
+        if !project.auto_detect() || options.policy == SetupPolicy::Never {
+            return Ok(SetupReport::default());
+        }
+
+        let repo_root = git::find_main_repo_root(worktree_path)?
+            .unwrap_or_else(|| worktree_path.to_path_buf());
+        if let Some(envrc) = direnv::allow_if_trusted(&repo_root, worktree_path)? {
+            reporter.info(&envrc.message());
+        }
+
+        let config = crate::config::load_config()?.setup;
+        let detectors = detectors();
+        let steps = detect_steps(worktree_path, &detectors, config.max_depth);
+
+        if steps.is_empty() {
+            return Ok(SetupReport::default());
+        }
+
+        if let Some(running) = status::read_status(worktree_path).filter(|s| s.is_running()) {
+            reporter.info(&format!("Setup is already running (pid {}), see: wt status", running.pid));
+            return Ok(SetupReport::default());
+        }
+
+        let hash = inputs_hash(worktree_path, &steps, &detectors);
+        if options.policy == SetupPolicy::Auto
+            && db::setup_hash(&repo_root, worktree_path)?.as_deref() == Some(hash.as_str())
+        {
+            reporter.info("✓ Setup is up to date (lockfiles unchanged, use --setup to force)");
+            return Ok(SetupReport::default());
+        }
+
+        let background = options.background.unwrap_or(config.background);
+        if background && matches!(reporter, Reporter::Terminal) {
+            Self::spawn_background(worktree_path, options.policy)?;
+            return Ok(SetupReport::default());
+        }
+
+        for step in &steps {
+            let relative = step.dir.strip_prefix(worktree_path).unwrap_or(&step.dir);
+            let location = if relative.as_os_str().is_empty() { Path::new(".") } else { relative };
+            reporter.info(&format!("Detected {} in {}", step.detector, location.display()));
+        }
 
-        // 1. Environment Setup (mise or nvm)
-        if worktree_path.join("mise.toml").exists() || worktree_path.join(".mise.toml").exists() {
-            commands.push("mise install");
-        } else if worktree_path.join(".nvmrc").exists() {
-            commands.push("nvm use");
+        // Steps of one directory share a shell so `nvm use` affects the install after it
+        let mut dirs: Vec<&Path> = Vec::new();
+        for step in &steps {
+            if !dirs.contains(&step.dir.as_path()) {
+                dirs.push(&step.dir);
+            }
         }
 
-        // 2. Package Manager Setup
-        if worktree_path.join("pnpm-lock.yaml").exists() {
-            commands.push("pnpm install");
-        } else if worktree_path.join("yarn.lock").exists() {
-            commands.push("yarn install");
-        } else if worktree_path.join("package-lock.json").exists() {
-            commands.push("npm install");
+        let log_path = status::log_path(worktree_path)?;
+        if let Some(parent) = log_path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        let mut log = File::create(&log_path)
+            .with_context(|| format!("Failed to create {}", log_path.display()))?;
+
+        let mut setup_status = SetupStatus {
+            state: SetupState::Running,
+            pid: std::process::id(),
+            started_at: status::now_secs()?,
+            finished_at: None,
+            failed: None,
+        };
+        status::write_status(worktree_path, &setup_status)?;
 
-        if commands.is_empty() {
-            return Ok(());
+        // Setup commands see the same variables as hooks, e.g. WT_PORT for generated configs
+        let mut env = project.env.clone();
+        let ports = crate::ports::lookup(&repo_root, worktree_path, &project.ports)?;
+        env.extend(ports.iter().flat_map(|p| p.env()).map(|(key, value)| (key.to_string(), value)));
+
+        let shell = resolve_shell(&config, std::env::var("SHELL").ok());
+        let mise_installed = fsutil::find_in_path("mise").is_some();
+        let started = Instant::now();
+        let mut report = SetupReport { steps: Vec::new(), log: log_path.clone() };
+        for dir in dirs {
+            let commands: Vec<&str> = steps
+                .iter()
+                .filter(|s| s.dir == dir)
+                .map(|s| s.command.as_str())
+                .collect();
+            // Version managers set up in this directory or above it
+            let managers: Vec<&str> = steps
+                .iter()
+                .filter(|s| matches!(s.detector, "mise" | "nvm") && dir.starts_with(&s.dir))
+                .map(|s| s.detector)
+                .collect();
+            let script = build_script(&commands, &managers, config.activation, &shell, mise_installed);
+            let step = Self::run_in_dir(dir, &commands.join(" && "), &script, &shell, &env, &mut log, reporter);
+            let success = step.success;
+            report.steps.push(step);
+            if !success {
+                setup_status.failed = Some(commands.join(" && "));
+                break;
+            }
         }
 
-        // 3. Command Execution via Shell
-        // Source shell config to ensure version managers are available
-        if commands.iter().any(|&c| c == "nvm use" || c == "mise install") {
-            shell_cmd.push_str("source ~/.zshrc 2>/dev/null || source ~/.bashrc 2>/dev/null || true; ");
+        setup_status.state = if setup_status.failed.is_some() { SetupState::Failed } else { SetupState::Succeeded };
+        setup_status.finished_at = Some(status::now_secs()?);
+        status::write_status(worktree_path, &setup_status)?;
+
+        if report.succeeded() {
+            db::set_setup_hash(&repo_root, worktree_path, &hash)?;
+            reporter.info(&format!("✓ Setup completed in {:.1}s", started.elapsed().as_secs_f64()));
+            return Ok(report);
         }
-        
-        shell_cmd.push_str(&commands.join(" && "));
-        
-        println!("Running automatic setup: {}", shell_cmd);
 
-        let output = Command::new("zsh")
-            .arg("-c")
-            .arg(&shell_cmd)
+        // A failed setup is retried on the next switch
+        if let Reporter::Terminal = reporter {
+            report.print_summary(worktree_path);
+        }
+        if config.best_effort {
+            if let Reporter::Terminal = reporter {
+                eprintln!("Warning: Setup failed, full log: {}", log_path.display());
+            }
+            Ok(report)
+        } else {
+            Err(SetupFailed(report).into())
+        }
+    }
+
+    /// Continue setup in a detached `wt __setup` process that outlives this one
+    fn spawn_background(worktree_path: &Path, policy: SetupPolicy) -> Result<()> {
+        let exe = std::env::current_exe().context("Failed to locate the wt executable")?;
+        let mut cmd = Command::new(exe);
+        cmd.arg("__setup").arg(worktree_path);
+        if policy == SetupPolicy::Always {
+            cmd.arg("--setup");
+        }
+
+        let child = cmd
             .current_dir(worktree_path)
-            .output();
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .context("Failed to start background setup")?;
+
+        println!("Setup continues in the background (pid {})", child.id());
+        println!("  Log: {}", status::log_path(worktree_path)?.display());
+        println!("  To check on it, run: wt status");
+        Ok(())
+    }
+
+    /// Run the setup script of one directory, streaming its output to the log and
+    /// showing progress on the terminal
+    fn run_in_dir(
+        dir: &Path,
+        label: &str,
+        script: &str,
+        shell: &str,
+        env: &HashMap<String, String>,
+        log: &mut File,
+        reporter: &Reporter,
+    ) -> StepReport {
+        reporter.info(&format!("Running automatic setup in {}: {}", dir.display(), script));
+        reporter.send(JobEvent::Step(label.to_string()));
+        let _ = writeln!(log, "$ cd {} && {}", dir.display(), script);
+
+        let started = Instant::now();
+        let mut report = StepReport {
+            dir: dir.to_path_buf(),
+            command: label.to_string(),
+            duration: Duration::ZERO,
+            exit_code: None,
+            success: false,
+        };
 
-        match output {
-            Ok(output) if output.status.success() => {
-                println!("✓ Setup completed successfully");
-                Ok(())
+        let child = script_command(script, shell)
+            .current_dir(dir)
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                if let Reporter::Terminal = reporter {
+                    eprintln!("Warning: Could not run setup command: {}", e);
+                }
+                let _ = writeln!(log, "Could not run setup command: {}", e);
+                return report;
             }
-            Ok(output) => {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                eprintln!("Warning: Setup completed with issues.");
-                if !stdout.trim().is_empty() {
-                    println!("Output: {}", stdout);
+        };
+
+        // Merge stdout and stderr line by line
+        let (tx, rx) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    let _ = tx.send(line);
+                }
+            });
+        }
+        if let Some(stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    let _ = tx.send(line);
+                }
+            });
+        }
+
+        let interactive = matches!(reporter, Reporter::Terminal) && std::io::stderr().is_terminal();
+        let mut spinner = interactive.then(|| Spinner::new(label.to_string()));
+        let mut tail: VecDeque<String> = VecDeque::new();
+
+        loop {
+            match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(line) => {
+                    let _ = writeln!(log, "{}", line);
+                    match (&mut spinner, reporter) {
+                        (Some(spinner), _) => spinner.update(&line),
+                        // Prefixed so output can't be mistaken for the cd directive
+                        (None, Reporter::Terminal) => println!("│ {}", line),
+                        (None, Reporter::Job { .. }) => reporter.send(JobEvent::Line(line.clone())),
+                    }
+                    if tail.len() == SHOWN_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
                 }
-                if !stderr.trim().is_empty() {
-                    eprintln!("Error output: {}", stderr);
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if let Some(spinner) = &mut spinner {
+                        spinner.tick();
+                    }
                 }
-                Ok(())
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
             }
-            Err(e) => {
-                eprintln!("Warning: Could not run setup command: {}", e);
-                Ok(())
+        }
+
+        if let Ok(exit) = child.wait() {
+            report.exit_code = exit.code();
+            report.success = exit.success();
+        }
+        report.duration = started.elapsed();
+        if let Some(spinner) = spinner {
+            spinner.finish();
+        }
+
+        if let Reporter::Job { .. } = reporter {
+            return report;
+        }
+        if report.success {
+            println!("✓ {} ({:.1}s)", label, report.duration.as_secs_f64());
+        } else {
+            eprintln!("✗ {} failed", label);
+            // The spinner only showed the latest line; show how it ended
+            if interactive {
+                for line in &tail {
+                    eprintln!("│ {}", line);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Set up several worktrees at once, at most `parallelism` at a time, with one
+    /// live status line per worktree
+    pub fn run_jobs(jobs: &[SetupJob], parallelism: usize) -> Result<()> {
+        let (tx, rx) = mpsc::channel();
+        let next = AtomicUsize::new(0);
+        let mut board = JobBoard::new(jobs);
+
+        let results = std::thread::scope(|scope| {
+            for _ in 0..parallelism.clamp(1, jobs.len().max(1)) {
+                let tx = tx.clone();
+                let next = &next;
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+                    let reporter = Reporter::Job { index, events: tx.clone() };
+                    let result = git::find_main_repo_root(&job.path)
+                        .and_then(|root| {
+                            let root = root.unwrap_or_else(|| job.path.clone());
+                            crate::config::load_project_config(&root, &job.path)
+                        })
+                        .and_then(|project| Self::run(&job.path, &project, &reporter));
+                    reporter.send(JobEvent::Finished(result));
+                });
+            }
+            drop(tx);
+
+            let mut results: Vec<Option<Result<SetupReport>>> = jobs.iter().map(|_| None).collect();
+            loop {
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok((index, JobEvent::Finished(result))) => {
+                        board.finish(index, &result);
+                        results[index] = Some(result);
+                    }
+                    Ok((index, event)) => board.update(index, event),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                board.draw();
+            }
+            results
+        });
+
+        board.clear();
+        board.print_summary();
+
+        // Report the first failure; setup failures take precedence for the exit code
+        let mut errors: Vec<anyhow::Error> = results.into_iter().flatten().filter_map(|r| r.err()).collect();
+        match errors.iter().position(|e| e.downcast_ref::<SetupFailed>().is_some()) {
+            Some(position) => Err(errors.swap_remove(position)),
+            None => errors.into_iter().next().map_or(Ok(()), Err),
+        }
+    }
+}
+
+enum JobState {
+    Queued,
+    Running { step: String, last_line: String, started: Instant },
+    Done { outcome: String, duration: Duration, success: bool },
+}
+
+/// Live status view of `SetupManager::run_jobs`: one line per worktree redrawn in place
+/// on a terminal, otherwise a line per state change
+struct JobBoard {
+    names: Vec<String>,
+    states: Vec<JobState>,
+    started: Vec<Instant>,
+    interactive: bool,
+    drawn: usize,
+    frame: usize,
+}
+
+impl JobBoard {
+    fn new(jobs: &[SetupJob]) -> Self {
+        Self {
+            names: jobs.iter().map(|j| j.name.clone()).collect(),
+            states: jobs.iter().map(|_| JobState::Queued).collect(),
+            started: jobs.iter().map(|_| Instant::now()).collect(),
+            interactive: std::io::stderr().is_terminal(),
+            drawn: 0,
+            frame: 0,
+        }
+    }
+
+    fn update(&mut self, index: usize, event: JobEvent) {
+        match event {
+            JobEvent::Step(step) => {
+                if !self.interactive {
+                    println!("[{}] {}", self.names[index], step);
+                }
+                if !matches!(self.states[index], JobState::Running { .. }) {
+                    self.started[index] = Instant::now();
+                }
+                self.states[index] = JobState::Running { step, last_line: String::new(), started: self.started[index] };
+            }
+            JobEvent::Line(line) => {
+                if let JobState::Running { last_line, .. } = &mut self.states[index] {
+                    *last_line = line.trim().to_string();
+                }
+            }
+            JobEvent::Finished(_) => {}
+        }
+    }
+
+    fn finish(&mut self, index: usize, result: &Result<SetupReport>) {
+        let duration = match &self.states[index] {
+            JobState::Running { started, .. } => started.elapsed(),
+            _ => Duration::ZERO,
+        };
+        let (outcome, success) = match result {
+            Ok(report) if report.steps.is_empty() => ("nothing to run".to_string(), true),
+            Ok(report) if report.succeeded() => ("ok".to_string(), true),
+            Ok(report) => (format!("failed (best effort), see {}", report.log.display()), true),
+            Err(e) => (format!("{:#}", e), false),
+        };
+        if !self.interactive {
+            println!("[{}] {} {}", self.names[index], if success { "✓" } else { "✗" }, outcome);
+        }
+        self.states[index] = JobState::Done { outcome, duration, success };
+    }
+
+    fn line(&self, index: usize) -> String {
+        let name = &self.names[index];
+        let width = self.names.iter().map(|n| n.len()).max().unwrap_or(0);
+        match &self.states[index] {
+            JobState::Queued => format!("· {:<width$}  queued", name, width = width),
+            JobState::Running { step, last_line, started } => format!(
+                "{} {:<width$}  {} ({}s) {}",
+                Spinner::FRAMES[self.frame],
+                name,
+                step,
+                started.elapsed().as_secs(),
+                last_line,
+                width = width
+            ),
+            JobState::Done { outcome, duration, success } => format!(
+                "{} {:<width$}  {} ({:.1}s)",
+                if *success { "✓" } else { "✗" },
+                name,
+                outcome,
+                duration.as_secs_f64(),
+                width = width
+            ),
+        }
+    }
+
+    fn draw(&mut self) {
+        if !self.interactive {
+            return;
+        }
+        self.frame = (self.frame + 1) % Spinner::FRAMES.len();
+
+        let width = terminal_width();
+        let mut stderr = std::io::stderr();
+        if self.drawn > 0 {
+            let _ = write!(stderr, "\x1b[{}A", self.drawn);
+        }
+        for index in 0..self.names.len() {
+            let _ = writeln!(stderr, "\r\x1b[2K{}", truncate(&self.line(index), width));
+        }
+        let _ = stderr.flush();
+        self.drawn = self.names.len();
+    }
+
+    fn clear(&mut self) {
+        if self.drawn == 0 {
+            return;
+        }
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\x1b[{}A\x1b[J", self.drawn);
+        let _ = stderr.flush();
+        self.drawn = 0;
+    }
+
+    fn print_summary(&self) {
+        println!("Setup summary:");
+        for index in 0..self.names.len() {
+            println!("  {}", self.line(index));
+        }
+    }
+}
+
+fn terminal_width() -> usize {
+    crossterm::terminal::size()
+        .ok()
+        .map(|(w, _)| w as usize)
+        .filter(|&w| w > 0)
+        .unwrap_or(80)
+}
+
+/// Cut `text` to fit in `width` columns (counting characters)
+fn truncate(text: &str, width: usize) -> &str {
+    match text.char_indices().nth(width.saturating_sub(1)) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// Shells that understand the `&&` and `.` used in setup scripts
+const POSIX_SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh"];
+
+/// Shell for setup scripts: `setup.shell` from config, then the login shell ($SHELL)
+/// if it is POSIX-compatible, then sh
+fn resolve_shell(config: &SetupConfig, login_shell: Option<String>) -> String {
+    config
+        .shell
+        .clone()
+        .or_else(|| {
+            login_shell.filter(|shell| {
+                let name = Path::new(shell).file_name().unwrap_or_default().to_string_lossy();
+                POSIX_SHELLS.contains(&name.as_ref())
+            })
+        })
+        .unwrap_or_else(|| "sh".to_string())
+}
+
+/// Turn the commands of one directory into a script that has the detected version
+/// managers available without going through interactive shell startup files
+fn build_script(commands: &[&str], managers: &[&str], activation: Activation, shell: &str, mise_installed: bool) -> String {
+    let mut parts: Vec<String> = Vec::new();
+
+    match activation {
+        Activation::Auto => {
+            // nvm is a shell function and only exists after sourcing nvm.sh
+            if managers.contains(&"nvm") {
+                parts.push(r#". "${NVM_DIR:-$HOME/.nvm}/nvm.sh""#.to_string());
+                parts.push("nvm use".to_string());
+            }
+            let use_mise = managers.contains(&"mise") && mise_installed;
+            for &command in commands.iter().filter(|&&c| c != "nvm use") {
+                if use_mise && command != "mise install" {
+                    parts.push(format!("mise exec -- {}", command));
+                } else {
+                    parts.push(command.to_string());
+                }
             }
         }
+        Activation::Rcfile => {
+            let name = Path::new(shell).file_name().unwrap_or_default().to_string_lossy();
+            let rcfile = match name.as_ref() {
+                "zsh" => "~/.zshrc",
+                "bash" => "~/.bashrc",
+                _ => "~/.profile",
+            };
+            parts.push(format!(". {} 2>/dev/null || true", rcfile));
+            parts.extend(commands.iter().map(|c| c.to_string()));
+        }
+        Activation::None => parts.extend(commands.iter().map(|c| c.to_string())),
+    }
+
+    parts.join(" && ")
+}
+
+/// Command running `script`: directly if it is a plain command line, otherwise via `shell -c`
+fn script_command(script: &str, shell: &str) -> Command {
+    let is_plain = script
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.' | '/' | ':' | '@'));
+
+    if is_plain {
+        let mut words = script.split_whitespace();
+        if let Some(program) = words.next() {
+            let mut cmd = Command::new(program);
+            cmd.args(words);
+            return cmd;
+        }
+    }
+
+    let mut cmd = Command::new(shell);
+    cmd.arg("-c").arg(script);
+    cmd
+}
+
+/// Lines of output shown when a setup command fails
+const SHOWN_TAIL_LINES: usize = 20;
+
+/// One-line progress indicator on stderr showing the latest output line
+struct Spinner {
+    label: String,
+    started: Instant,
+    frame: usize,
+    last_line: String,
+}
+
+impl Spinner {
+    const FRAMES: &'static [char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+    fn new(label: String) -> Self {
+        let spinner = Self { label, started: Instant::now(), frame: 0, last_line: String::new() };
+        spinner.draw();
+        spinner
+    }
+
+    fn update(&mut self, line: &str) {
+        self.last_line = line.trim().to_string();
+        self.draw();
+    }
+
+    fn tick(&mut self) {
+        self.frame = (self.frame + 1) % Self::FRAMES.len();
+        self.draw();
+    }
+
+    fn draw(&self) {
+        let text = format!(
+            "{} {} ({}s) {}",
+            Self::FRAMES[self.frame],
+            self.label,
+            self.started.elapsed().as_secs(),
+            self.last_line
+        );
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K{}", truncate(&text, terminal_width()));
+        let _ = stderr.flush();
+    }
+
+    fn finish(self) {
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Sandbox;
+
+    fn hash_of(root: &Path) -> String {
+        let detectors = detectors();
+        let steps = detect_steps(root, &detectors, 2);
+        inputs_hash(root, &steps, &detectors)
+    }
+
+    fn write(root: &Path, file: &str, content: &str) {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn inputs_hash_tracks_lockfiles_only() {
+        let first = Sandbox::new("hash-a");
+        let second = Sandbox::new("hash-b");
+        for dir in [&first, &second] {
+            write(&dir.root, "pnpm-lock.yaml", "lockfileVersion: 9\n");
+            write(&dir.root, "api/Cargo.toml", "[package]\n");
+            write(&dir.root, "api/Cargo.lock", "version = 3\n");
+        }
+
+        // Worktrees of the same commit live at different paths but share the hash
+        let hash = hash_of(&first.root);
+        assert_eq!(hash, hash_of(&second.root));
+
+        write(&second.root, "src/index.ts", "console.log(1)\n");
+        assert_eq!(hash, hash_of(&second.root));
+
+        write(&second.root, "api/Cargo.lock", "version = 4\n");
+        assert_ne!(hash, hash_of(&second.root));
+    }
+
+    #[test]
+    fn inputs_hash_tracks_tool_versions_and_steps() {
+        let dir = Sandbox::new("hash-tools");
+        write(&dir.root, "package-lock.json", "{}\n");
+        let before = hash_of(&dir.root);
+
+        write(&dir.root, ".node-version", "22\n");
+        let with_version = hash_of(&dir.root);
+        assert_ne!(before, with_version);
+
+        // A new subproject adds a step
+        write(&dir.root, "tools/uv.lock", "\n");
+        assert_ne!(with_version, hash_of(&dir.root));
+    }
+
+    #[test]
+    fn auto_activation_loads_nvm_and_wraps_commands_in_mise() {
+        let script = build_script(&["nvm use", "npm install"], &["nvm"], Activation::Auto, "sh", false);
+        assert_eq!(script, r#". "${NVM_DIR:-$HOME/.nvm}/nvm.sh" && nvm use && npm install"#);
+
+        let commands = ["mise install", "pnpm install"];
+        assert_eq!(
+            build_script(&commands, &["mise"], Activation::Auto, "sh", true),
+            "mise install && mise exec -- pnpm install"
+        );
+        // Without mise on PATH the commands run as they are
+        assert_eq!(
+            build_script(&commands, &["mise"], Activation::Auto, "sh", false),
+            "mise install && pnpm install"
+        );
+    }
+
+    #[test]
+    fn rcfile_and_none_activation() {
+        let commands = ["nvm use", "npm install"];
+        assert_eq!(
+            build_script(&commands, &["nvm"], Activation::Rcfile, "/bin/zsh", true),
+            ". ~/.zshrc 2>/dev/null || true && nvm use && npm install"
+        );
+        assert_eq!(
+            build_script(&commands, &["nvm"], Activation::Rcfile, "/bin/bash", true),
+            ". ~/.bashrc 2>/dev/null || true && nvm use && npm install"
+        );
+        assert_eq!(
+            build_script(&commands, &["nvm"], Activation::Rcfile, "sh", true),
+            ". ~/.profile 2>/dev/null || true && nvm use && npm install"
+        );
+        assert_eq!(build_script(&commands, &["nvm"], Activation::None, "sh", true), "nvm use && npm install");
+    }
+
+    #[test]
+    fn shell_falls_back_to_sh_for_non_posix_login_shells() {
+        let config = SetupConfig::default();
+        assert_eq!(resolve_shell(&config, Some("/usr/bin/zsh".to_string())), "/usr/bin/zsh");
+        assert_eq!(resolve_shell(&config, Some("/usr/bin/fish".to_string())), "sh");
+        assert_eq!(resolve_shell(&config, Some("/usr/bin/nu".to_string())), "sh");
+        assert_eq!(resolve_shell(&config, None), "sh");
+
+        let config = SetupConfig { shell: Some("/bin/bash".to_string()), ..Default::default() };
+        assert_eq!(resolve_shell(&config, Some("/usr/bin/fish".to_string())), "/bin/bash");
+    }
+
+    #[test]
+    fn plain_scripts_skip_the_shell() {
+        let cmd = script_command("pnpm install --frozen-lockfile", "bash");
+        assert_eq!(cmd.get_program(), "pnpm");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["install", "--frozen-lockfile"]);
+
+        let cmd = script_command("nvm use && npm install", "bash");
+        assert_eq!(cmd.get_program(), "bash");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-c", "nvm use && npm install"]);
+    }
+
+    fn detected(root: &Path, max_depth: usize) -> Vec<(String, &'static str)> {
+        detect_steps(root, &detectors(), max_depth)
+            .into_iter()
+            .map(|step| {
+                let relative = step.dir.strip_prefix(root).unwrap().to_string_lossy().to_string();
+                (relative, step.detector)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_subprojects_within_max_depth() {
+        let dir = Sandbox::new("detect");
+        write(&dir.root, ".nvmrc", "22\n");
+        write(&dir.root, "pnpm-lock.yaml", "\n");
+        write(&dir.root, "services/api/Cargo.toml", "[package]\n");
+        write(&dir.root, "services/api/deep/go.mod", "module x\n");
+        // A nested lockfile of a group the root already covers adds no step
+        write(&dir.root, "web/package-lock.json", "{}\n");
+
+        assert_eq!(detected(&dir.root, 0), [("".to_string(), "nvm"), ("".to_string(), "pnpm")]);
+        assert_eq!(
+            detected(&dir.root, 2),
+            [("".to_string(), "nvm"), ("".to_string(), "pnpm"), ("services/api".to_string(), "cargo")]
+        );
+        assert_eq!(detected(&dir.root, 3).last().unwrap(), &("services/api/deep".to_string(), "go"));
+    }
+
+    #[test]
+    fn skips_dependency_and_hidden_directories() {
+        let dir = Sandbox::new("detect-skip");
+        for skipped in SKIP_DIRS {
+            write(&dir.root, &format!("{}/pkg/Cargo.toml", skipped), "[package]\n");
+        }
+        write(&dir.root, ".cache/go.mod", "module x\n");
+        assert!(detected(&dir.root, 3).is_empty());
+
+        write(&dir.root, "crates/core/Cargo.toml", "[package]\n");
+        assert_eq!(detected(&dir.root, 3), [("crates/core".to_string(), "cargo")]);
     }
 }
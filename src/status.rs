@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::git;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SetupState {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Outcome of the last automatic setup of a worktree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetupStatus {
+    pub state: SetupState,
+    /// Process running the setup
+    pub pid: u32,
+    pub started_at: u64,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    /// Command that failed
+    #[serde(default)]
+    pub failed: Option<String>,
+}
+
+impl SetupStatus {
+    /// A running setup whose process is gone was interrupted
+    pub fn is_running(&self) -> bool {
+        self.state == SetupState::Running && process_alive(self.pid)
+    }
+}
+
+fn process_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+pub fn now_secs() -> Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
+
+/// Directory holding setup logs and status of a worktree:
+/// ~/.wt-manager/logs/<worktree dir name>_<path hash>
+pub fn log_dir(worktree_path: &Path) -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Failed to get home directory")?;
+    let name = worktree_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(worktree_path.to_string_lossy().as_bytes());
+    let hash = hex::encode(&hasher.finalize()[..4]);
+
+    Ok(home.join(".wt-manager").join("logs").join(format!("{}_{}", name, hash)))
+}
+
+pub fn log_path(worktree_path: &Path) -> Result<PathBuf> {
+    Ok(log_dir(worktree_path)?.join("setup.log"))
+}
+
+pub fn read_status(worktree_path: &Path) -> Option<SetupStatus> {
+    let path = log_dir(worktree_path).ok()?.join("status.json");
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn write_status(worktree_path: &Path, status: &SetupStatus) -> Result<()> {
+    let dir = log_dir(worktree_path)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("status.json"), serde_json::to_string_pretty(status)?)?;
+    Ok(())
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+/// Show the setup state of every worktree of a repository
+pub fn handle_status(repo_root: &Path) -> Result<()> {
+    let worktrees = git::list_worktrees(repo_root)?;
+    let now = now_secs()?;
+
+    let width = worktrees.iter().map(|wt| wt.name().len()).max().unwrap_or(0).max(8);
+    println!("{:<width$}  {:<12}  DETAIL", "WORKTREE", "SETUP", width = width);
+
+    for wt in &worktrees {
+        let (state, detail) = match read_status(&wt.path) {
+            None => ("-".to_string(), String::new()),
+            Some(status) => {
+                let state = match status.state {
+                    SetupState::Running if status.is_running() => "running",
+                    SetupState::Running => "interrupted",
+                    SetupState::Succeeded => "ok",
+                    SetupState::Failed => "failed",
+                };
+                let detail = if matches!(state, "failed" | "interrupted") {
+                    let log = log_path(&wt.path)?;
+                    match &status.failed {
+                        Some(command) => format!("`{}` failed, see {}", command, log.display()),
+                        None => format!("see {}", log.display()),
+                    }
+                } else if let Some(finished_at) = status.finished_at {
+                    format_age(now.saturating_sub(finished_at))
+                } else {
+                    format!("started {}", format_age(now.saturating_sub(status.started_at)))
+                };
+                (state.to_string(), detail)
+            }
+        };
+        println!("{:<width$}  {:<12}  {}", wt.name(), state, detail, width = width);
+    }
+
+    Ok(())
+}
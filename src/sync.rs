@@ -0,0 +1,182 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::config::{self, SyncStrategy};
+use crate::{db, git};
+
+pub struct SyncOptions {
+    /// Only these branches (all worktrees when empty)
+    pub branches: Vec<String>,
+    /// Overrides `sync.strategy` from config
+    pub strategy: Option<SyncStrategy>,
+    /// Don't fetch remotes first
+    pub no_fetch: bool,
+}
+
+/// What happened to one worktree
+enum Outcome {
+    UpToDate,
+    Synced { commits: usize },
+    SkippedDirty,
+    /// Rebase/merge was aborted, or the base couldn't be found
+    Failed(String),
+}
+
+impl Outcome {
+    fn label(&self, strategy: SyncStrategy) -> String {
+        match self {
+            Outcome::UpToDate => "up to date".to_string(),
+            Outcome::Synced { commits } => {
+                let verb = match strategy {
+                    SyncStrategy::Rebase => "rebased",
+                    SyncStrategy::Merge => "merged",
+                };
+                format!("{} ({} new commit{})", verb, commits, if *commits == 1 { "" } else { "s" })
+            }
+            Outcome::SkippedDirty => "skipped: uncommitted changes".to_string(),
+            Outcome::Failed(reason) => format!("needs attention: {}", reason),
+        }
+    }
+}
+
+/// Base a worktree's branch is kept current with: the recorded base, else the default branch.
+/// A local base branch is replaced by its upstream, which is what the fetch updated.
+fn sync_base(repo_root: &Path, branch: &str) -> Result<String> {
+    let base = match db::worktree_base(repo_root, branch)? {
+        Some(base) => base,
+        None => git::default_branch(repo_root)?,
+    };
+
+    if git::ref_exists(repo_root, &format!("refs/heads/{}", base)) {
+        if let Some(upstream) = git::upstream(repo_root, &base) {
+            return Ok(upstream);
+        }
+    }
+    Ok(base)
+}
+
+fn sync_worktree(repo_root: &Path, wt: &git::WorktreeInfo, base: &str, strategy: SyncStrategy) -> Result<Outcome> {
+    if !git::ref_exists(repo_root, base) {
+        return Ok(Outcome::Failed(format!("base '{}' not found", base)));
+    }
+    if git::is_dirty(&wt.path)? {
+        return Ok(Outcome::SkippedDirty);
+    }
+
+    let commits = git::count_commits(&wt.path, &format!("HEAD..{}", base));
+    if commits == 0 {
+        return Ok(Outcome::UpToDate);
+    }
+
+    match git::integrate(&wt.path, base, strategy == SyncStrategy::Merge) {
+        Ok(()) => Ok(Outcome::Synced { commits }),
+        Err(e) => Ok(Outcome::Failed(e.to_string())),
+    }
+}
+
+/// Fetch once, then rebase or merge every clean worktree onto its base
+pub fn handle_sync(repo_root: &Path, options: &SyncOptions) -> Result<()> {
+    let strategy = match options.strategy {
+        Some(strategy) => strategy,
+        None => config::load_config()?.sync.strategy,
+    };
+
+    let worktrees: Vec<git::WorktreeInfo> = git::list_worktrees(repo_root)?
+        .into_iter()
+        .filter(|wt| !wt.is_main && !wt.detached && !wt.prunable)
+        .filter(|wt| options.branches.is_empty() || options.branches.contains(&wt.branch))
+        .collect();
+
+    for branch in &options.branches {
+        if !worktrees.iter().any(|wt| &wt.branch == branch) {
+            anyhow::bail!("No worktree found for '{}'", branch);
+        }
+    }
+    if worktrees.is_empty() {
+        println!("No worktrees to sync.");
+        return Ok(());
+    }
+
+    if !options.no_fetch {
+        println!("Fetching...");
+        git::fetch_all(repo_root)?;
+    }
+
+    // A worktree that can't be synced is reported like a conflict; the others still run
+    let mut results = Vec::new();
+    for wt in &worktrees {
+        let (base, outcome) = match sync_base(repo_root, &wt.branch) {
+            Ok(base) => {
+                let outcome = sync_worktree(repo_root, wt, &base, strategy)
+                    .unwrap_or_else(|e| Outcome::Failed(format!("{:#}", e)));
+                (Some(base), outcome)
+            }
+            Err(e) => (None, Outcome::Failed(format!("{:#}", e))),
+        };
+        let mark = match outcome {
+            Outcome::UpToDate | Outcome::Synced { .. } => "✓",
+            Outcome::SkippedDirty => "-",
+            Outcome::Failed(_) => "✗",
+        };
+        println!("{} {}: {}", mark, wt.branch, outcome.label(strategy));
+        results.push((wt, base, outcome));
+    }
+
+    let width = worktrees.iter().map(|wt| wt.branch.len()).max().unwrap_or(0).max(8);
+    let base_width = results
+        .iter()
+        .map(|(_, base, _)| base.as_deref().map_or(1, str::len))
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!();
+    println!("{:<width$}  {:<base_width$}  RESULT", "WORKTREE", "BASE", width = width, base_width = base_width);
+    for (wt, base, outcome) in &results {
+        println!(
+            "{:<width$}  {:<base_width$}  {}",
+            wt.branch,
+            base.as_deref().unwrap_or("-"),
+            outcome.label(strategy),
+            width = width,
+            base_width = base_width
+        );
+    }
+
+    let failed: Vec<_> = results
+        .iter()
+        .filter(|(_, _, outcome)| matches!(outcome, Outcome::Failed(_)))
+        .collect();
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    let command = match strategy {
+        SyncStrategy::Rebase => "rebase",
+        SyncStrategy::Merge => "merge",
+    };
+    println!("\nThese worktrees were left unchanged. To resolve them by hand, run:");
+    for (wt, base, _) in &failed {
+        match base {
+            Some(base) => println!("  git -C {} {} {}", wt.path.display(), command, base),
+            None => println!("  git -C {} {} <base>", wt.path.display(), command),
+        }
+    }
+    anyhow::bail!("{} of {} worktrees need attention", failed.len(), results.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_labels() {
+        assert_eq!(Outcome::UpToDate.label(SyncStrategy::Rebase), "up to date");
+        assert_eq!(Outcome::Synced { commits: 1 }.label(SyncStrategy::Rebase), "rebased (1 new commit)");
+        assert_eq!(Outcome::Synced { commits: 3 }.label(SyncStrategy::Merge), "merged (3 new commits)");
+        assert_eq!(Outcome::SkippedDirty.label(SyncStrategy::Merge), "skipped: uncommitted changes");
+        assert_eq!(
+            Outcome::Failed("conflicts in README".to_string()).label(SyncStrategy::Rebase),
+            "needs attention: conflicts in README"
+        );
+    }
+}
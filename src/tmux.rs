@@ -0,0 +1,177 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::{TmuxConfig, TmuxMode};
+use crate::fsutil;
+
+/// Session (or window) name for a worktree: `{project}/{branch}`.
+/// tmux doesn't allow `.` and `:` in names.
+pub fn session_name(repo_root: &Path, name: &str) -> String {
+    let project = repo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("{}/{}", project, name)
+        .chars()
+        .map(|c| if matches!(c, '.' | ':' | ' ') { '_' } else { c })
+        .collect()
+}
+
+fn tmux(args: &[&str]) -> Result<String> {
+    let output = Command::new("tmux").args(args).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("tmux {} failed: {}", args.first().unwrap_or(&""), stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn has_session(name: &str) -> bool {
+    tmux(&["has-session", "-t", &format!("={}", name)]).is_ok()
+}
+
+/// Id of the window called `name` in any session
+fn find_window(name: &str) -> Option<String> {
+    let windows = tmux(&["list-windows", "-a", "-F", "#{window_id} #{window_name}"]).ok()?;
+    windows.lines().find_map(|line| {
+        let (id, window_name) = line.split_once(' ')?;
+        (window_name == name).then(|| id.to_string())
+    })
+}
+
+/// Pane wt runs in, if it runs inside tmux
+fn current_pane() -> Option<String> {
+    std::env::var_os("TMUX")?;
+    std::env::var("TMUX_PANE").ok()
+}
+
+fn inside_tmux() -> bool {
+    current_pane().is_some()
+}
+
+/// Add the configured panes to a fresh window whose first pane is `first_pane`
+fn build_layout(first_pane: &str, worktree_path: &Path, config: &TmuxConfig) -> Result<()> {
+    let path = worktree_path.to_string_lossy();
+    let panes = config.panes.as_deref().unwrap_or_default();
+
+    let mut pane_ids = vec![first_pane.to_string()];
+    for _ in panes.iter().skip(1) {
+        let id = tmux(&["split-window", "-t", first_pane, "-c", &path, "-P", "-F", "#{pane_id}"])?;
+        pane_ids.push(id);
+    }
+
+    if pane_ids.len() > 1 {
+        let layout = config.layout.as_deref().unwrap_or("tiled");
+        tmux(&["select-layout", "-t", first_pane, layout])?;
+    }
+
+    // Commands are typed into the panes so they stay usable once the command exits
+    for (id, command) in pane_ids.iter().zip(panes) {
+        if !command.is_empty() {
+            tmux(&["send-keys", "-t", id, command, "Enter"])?;
+        }
+    }
+
+    tmux(&["select-pane", "-t", first_pane])?;
+    Ok(())
+}
+
+/// Attach to (or create) the tmux session or window of a worktree
+pub fn open(repo_root: &Path, worktree_path: &Path, name: &str, config: &TmuxConfig) -> Result<()> {
+    if !config.enabled() {
+        return Ok(());
+    }
+    if fsutil::find_in_path("tmux").is_none() {
+        eprintln!("Warning: tmux integration is enabled, but tmux is not installed");
+        return Ok(());
+    }
+
+    let target = session_name(repo_root, name);
+    let path = worktree_path.to_string_lossy();
+
+    // Windows live in the current session, so outside tmux a session is used instead
+    if config.mode.unwrap_or_default() == TmuxMode::Window && inside_tmux() {
+        match find_window(&target) {
+            Some(id) => {
+                tmux(&["select-window", "-t", &id])?;
+            }
+            None => {
+                let pane = tmux(&["new-window", "-n", &target, "-c", &path, "-P", "-F", "#{pane_id}"])?;
+                build_layout(&pane, worktree_path, config)?;
+            }
+        }
+        println!("✓ tmux window '{}'", target);
+        return Ok(());
+    }
+
+    if !has_session(&target) {
+        let pane = tmux(&["new-session", "-d", "-s", &target, "-c", &path, "-P", "-F", "#{pane_id}"])?;
+        build_layout(&pane, worktree_path, config)?;
+        println!("✓ Created tmux session '{}'", target);
+    }
+
+    let switched = inside_tmux() && tmux(&["switch-client", "-t", &format!("={}", target)]).is_ok();
+    if !switched {
+        // The shell wrapper attaches after changing directory
+        println!("\nTo attach to its tmux session, run:");
+        println!("  tmux attach -t {}", target);
+    }
+
+    Ok(())
+}
+
+/// Kill the tmux session or window of a removed worktree, unless wt runs inside it
+pub fn close(repo_root: &Path, name: &str, config: &TmuxConfig) {
+    if !config.enabled() || fsutil::find_in_path("tmux").is_none() {
+        return;
+    }
+
+    let target = session_name(repo_root, name);
+    let current = |format: &str| {
+        current_pane().and_then(|pane| tmux(&["display-message", "-p", "-t", &pane, format]).ok())
+    };
+    let current_session = current("#S");
+    let current_window = current("#{window_id}");
+
+    if has_session(&target) {
+        if current_session.as_deref() == Some(target.as_str()) {
+            println!("Keeping tmux session '{}' because wt runs inside it", target);
+        } else if tmux(&["kill-session", "-t", &format!("={}", target)]).is_ok() {
+            println!("✓ Killed tmux session '{}'", target);
+        }
+    }
+
+    if let Some(id) = find_window(&target) {
+        if current_window.as_deref() == Some(id.as_str()) {
+            println!("Keeping tmux window '{}' because wt runs inside it", target);
+        } else if tmux(&["kill-window", "-t", &id]).is_ok() {
+            println!("✓ Killed tmux window '{}'", target);
+        }
+    }
+}
+
+/// Follow a renamed worktree, so `wt switch` finds its session or window again.
+/// Shells in the panes keep their old working directory.
+pub fn rename(repo_root: &Path, old: &str, new: &str, config: &TmuxConfig) {
+    if !config.enabled() || fsutil::find_in_path("tmux").is_none() {
+        return;
+    }
+
+    let old_target = session_name(repo_root, old);
+    let new_target = session_name(repo_root, new);
+
+    if has_session(&old_target)
+        && tmux(&["rename-session", "-t", &format!("={}", old_target), &new_target]).is_ok()
+    {
+        println!("✓ Renamed tmux session '{}' to '{}'", old_target, new_target);
+    }
+
+    if let Some(id) = find_window(&old_target) {
+        if tmux(&["rename-window", "-t", &id, &new_target]).is_ok() {
+            println!("✓ Renamed tmux window '{}' to '{}'", old_target, new_target);
+        }
+    }
+}
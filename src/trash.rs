@@ -0,0 +1,293 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{db, fsutil, git};
+
+/// A removed worktree kept for `wt undo`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashEntry {
+    pub id: String,
+    pub repo: PathBuf,
+    /// Worktree name (branch, or short commit for detached worktrees)
+    pub name: String,
+    pub branch: Option<String>,
+    /// Commit checked out when the worktree was removed
+    pub head: String,
+    /// Where the worktree lived
+    pub path: PathBuf,
+    pub trashed_at: u64,
+    /// Saved untracked paths, relative to the worktree
+    pub files: Vec<String>,
+}
+
+fn get_trash_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Failed to get home directory")?;
+    let trash_dir = home.join(".wt-manager").join("trash");
+    fs::create_dir_all(&trash_dir)?;
+    Ok(trash_dir)
+}
+
+/// Ref that keeps the trashed commit reachable until the entry is purged
+fn trash_ref(id: &str) -> String {
+    format!("refs/wt-trash/{}", id)
+}
+
+fn now_secs() -> Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
+
+/// Untracked files worth keeping: everything not ignored, plus ignored paths
+/// matching `trash.include`
+fn files_to_save(worktree_path: &Path, include: &[String]) -> Result<Vec<String>> {
+    let patterns: Vec<glob::Pattern> = include
+        .iter()
+        .map(|p| glob::Pattern::new(p).with_context(|| format!("Invalid trash.include pattern '{}'", p)))
+        .collect::<Result<_>>()?;
+
+    let mut files = git::list_untracked(worktree_path, false)?;
+    for ignored in git::list_untracked(worktree_path, true)? {
+        let trimmed = ignored.trim_end_matches('/');
+        let file_name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+        if patterns.iter().any(|p| p.matches(trimmed) || p.matches(file_name)) {
+            files.push(ignored);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Copy the untracked files, write meta.json and keep the commit, then remove the worktree
+fn fill_entry(
+    repo_root: &Path,
+    wt: &git::WorktreeInfo,
+    id: &str,
+    entry_dir: &Path,
+    trashed_at: u64,
+    include: &[String],
+) -> Result<TrashEntry> {
+    let files_dir = entry_dir.join("files");
+    fs::create_dir_all(&files_dir)?;
+
+    let files = files_to_save(&wt.path, include)?;
+    for file in &files {
+        let relative = file.trim_end_matches('/');
+        fsutil::copy_path(&wt.path.join(relative), &files_dir.join(relative))?;
+    }
+
+    let entry = TrashEntry {
+        id: id.to_string(),
+        repo: repo_root.to_path_buf(),
+        name: wt.name().to_string(),
+        branch: (!wt.detached).then(|| wt.branch.clone()),
+        head: wt.head.clone(),
+        path: wt.path.clone(),
+        trashed_at,
+        files,
+    };
+    fs::write(entry_dir.join("meta.json"), serde_json::to_string_pretty(&entry)?)?;
+    git::update_ref(repo_root, &trash_ref(id), &wt.head)?;
+
+    // Untracked files are safe in the trash now
+    git::remove_worktree(repo_root, &wt.path, true)?;
+    Ok(entry)
+}
+
+/// Save a worktree's untracked files and commit to the trash, then remove it
+pub fn trash_worktree(repo_root: &Path, wt: &git::WorktreeInfo) -> Result<TrashEntry> {
+    let config = crate::config::load_config()?.trash;
+
+    // Tracked changes aren't saved, so let them block removal like git does
+    if git::has_tracked_changes(&wt.path)? {
+        anyhow::bail!("Worktree '{}' has uncommitted changes", wt.name());
+    }
+
+    let trashed_at = now_secs()?;
+    let trash_dir = get_trash_dir()?;
+    fs::create_dir_all(&trash_dir)?;
+
+    // Removals within the same second get a numbered id; create_dir claims the id atomically
+    let base_id = format!("{}-{}", trashed_at, wt.name().replace('/', "-"));
+    let mut id = base_id.clone();
+    let mut suffix = 2;
+    while let Err(e) = fs::create_dir(trash_dir.join(&id)) {
+        if e.kind() != std::io::ErrorKind::AlreadyExists {
+            return Err(e.into());
+        }
+        id = format!("{}-{}", base_id, suffix);
+        suffix += 1;
+    }
+    let entry_dir = trash_dir.join(&id);
+
+    // Without meta.json `list` and `purge` never see the entry, so drop it on every error
+    let entry = match fill_entry(repo_root, wt, &id, &entry_dir, trashed_at, &config.include) {
+        Ok(entry) => entry,
+        Err(e) => {
+            let _ = git::delete_ref(repo_root, &trash_ref(&id));
+            let _ = fs::remove_dir_all(&entry_dir);
+            return Err(e);
+        }
+    };
+
+    purge_expired(config.retention_days)?;
+    Ok(entry)
+}
+
+/// Trash entries, newest first. With `repo` only entries of that repository.
+pub fn list_entries(repo: Option<&Path>) -> Result<Vec<TrashEntry>> {
+    let mut entries = Vec::new();
+
+    for dir in fs::read_dir(get_trash_dir()?)? {
+        let meta_path = dir?.path().join("meta.json");
+        let Ok(content) = fs::read_to_string(&meta_path) else {
+            continue;
+        };
+        let entry: TrashEntry = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", meta_path.display()))?;
+        if repo.is_none_or(|repo| entry.repo == repo) {
+            entries.push(entry);
+        }
+    }
+
+    entries.sort_by_key(|e| std::cmp::Reverse(e.trashed_at));
+    Ok(entries)
+}
+
+/// Delete a trash entry and release its commit
+fn delete_entry(entry: &TrashEntry) -> Result<()> {
+    if entry.repo.exists() {
+        git::delete_ref(&entry.repo, &trash_ref(&entry.id))?;
+    }
+    fs::remove_dir_all(get_trash_dir()?.join(&entry.id))?;
+    Ok(())
+}
+
+/// Recreate a trashed worktree and put its files back
+pub fn restore(entry: &TrashEntry) -> Result<()> {
+    if entry.path.exists() {
+        anyhow::bail!("Cannot restore '{}': {} already exists", entry.name, entry.path.display());
+    }
+
+    if let Some(parent) = entry.path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match &entry.branch {
+        Some(branch) if git::ref_exists(&entry.repo, &format!("refs/heads/{}", branch)) => {
+            git::add_worktree(&entry.repo, &entry.path, branch, git::AddMode::Existing)?;
+            if git::rev_parse(&entry.repo, branch).as_deref() != Some(entry.head.as_str()) {
+                println!("Note: branch '{}' moved since it was trashed (was {})", branch, entry.head);
+            }
+        }
+        Some(branch) => {
+            // The branch was deleted after the worktree; recreate it at the saved commit
+            git::add_worktree(&entry.repo, &entry.path, branch, git::AddMode::NewBranch { base: Some(&entry.head) })?;
+            println!("Recreated branch '{}' at {}", branch, entry.head);
+        }
+        None => {
+            git::add_worktree(&entry.repo, &entry.path, &entry.head, git::AddMode::Detach)?;
+        }
+    }
+
+    let files_dir = get_trash_dir()?.join(&entry.id).join("files");
+    for file in &entry.files {
+        let relative = file.trim_end_matches('/');
+        let target = entry.path.join(relative);
+        if target.exists() {
+            println!("Skipped {}: already exists", relative);
+            continue;
+        }
+        fsutil::copy_path(&files_dir.join(relative), &target)?;
+    }
+
+    db::record_worktree(&entry.repo, &entry.name, &entry.path, None)?;
+    delete_entry(entry)?;
+    Ok(())
+}
+
+/// Delete entries older than `days`. Returns the number of purged entries.
+pub fn purge_older_than(repo: Option<&Path>, days: u64) -> Result<usize> {
+    let cutoff = now_secs()?.saturating_sub(days * 24 * 60 * 60);
+    let mut purged = 0;
+
+    for entry in list_entries(repo)? {
+        if entry.trashed_at <= cutoff {
+            delete_entry(&entry)?;
+            purged += 1;
+        }
+    }
+
+    Ok(purged)
+}
+
+fn purge_expired(retention_days: u64) -> Result<()> {
+    purge_older_than(None, retention_days)?;
+    Ok(())
+}
+
+/// `wt undo`: restore the most recently trashed worktree of this repository
+pub fn handle_undo(repo_root: &Path) -> Result<()> {
+    let entry = list_entries(Some(repo_root))?
+        .into_iter()
+        .next()
+        .context("Trash is empty for this repository")?;
+
+    handle_restore(&entry)
+}
+
+/// `wt trash restore <id>`
+pub fn handle_restore_id(repo_root: &Path, id: &str) -> Result<()> {
+    let entry = list_entries(Some(repo_root))?
+        .into_iter()
+        .find(|e| e.id == id || e.name == id)
+        .with_context(|| format!("No trash entry '{}'", id))?;
+
+    handle_restore(&entry)
+}
+
+fn handle_restore(entry: &TrashEntry) -> Result<()> {
+    println!("Restoring worktree '{}' ({} saved file(s))", entry.name, entry.files.len());
+    restore(entry)?;
+    println!("✓ Restored worktree '{}'", entry.name);
+    crate::worktree::run_post_create(&entry.repo, &entry.path, &entry.name)?;
+    crate::worktree::switch_to_worktree(&entry.path)
+}
+
+/// `wt trash list`
+pub fn handle_list(repo_root: &Path) -> Result<()> {
+    let entries = list_entries(Some(repo_root))?;
+    if entries.is_empty() {
+        println!("Trash is empty");
+        return Ok(());
+    }
+
+    let now = now_secs()?;
+    for entry in entries {
+        let age_hours = now.saturating_sub(entry.trashed_at) / 3600;
+        println!(
+            "{}  {}  {} file(s)  {}h ago",
+            entry.id,
+            entry.name,
+            entry.files.len(),
+            age_hours
+        );
+    }
+
+    Ok(())
+}
+
+/// `wt trash purge`: delete expired entries, or all of this repository's with `all`
+pub fn handle_purge(repo_root: &Path, all: bool) -> Result<()> {
+    let days = if all {
+        0
+    } else {
+        crate::config::load_config()?.trash.retention_days
+    };
+
+    let purged = purge_older_than(Some(repo_root), days)?;
+    println!("✓ Purged {} trash entr{}", purged, if purged == 1 { "y" } else { "ies" });
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+use crate::config::{self, PROJECT_CONFIG_FILE};
+use crate::db;
+
+fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Whether `.wt.toml` with this content was approved for the project
+pub fn is_approved(repo_root: &Path, content: &str) -> Result<bool> {
+    Ok(db::project_config_approved(repo_root)?.is_some_and(|approved| approved == content_hash(content)))
+}
+
+/// Approve the main worktree's `.wt.toml`; worktrees with the same file run its commands too
+pub fn handle_trust(repo_root: &Path) -> Result<()> {
+    let path = repo_root.join(PROJECT_CONFIG_FILE);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("No {} in {}", PROJECT_CONFIG_FILE, repo_root.display()))?;
+    let project: config::ProjectConfig =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    db::set_project_config_approved(repo_root, Some(&content_hash(&content)))?;
+
+    println!("✓ Trusted {}", path.display());
+    let hooks = [
+        ("post_create", &project.hooks.post_create),
+        ("post_switch", &project.hooks.post_switch),
+        ("pre_remove", &project.hooks.pre_remove),
+        ("post_remove", &project.hooks.post_remove),
+    ];
+    for (name, commands) in hooks {
+        for command in commands.iter().flatten() {
+            println!("  {} hook: {}", name, command);
+        }
+    }
+    for command in project.tmux.panes.iter().flatten().filter(|c| !c.is_empty()) {
+        println!("  tmux pane: {}", command);
+    }
+    for pattern in project.files.copy.iter().flatten() {
+        println!("  copy: {}", pattern);
+    }
+    for pattern in project.files.symlink.iter().flatten() {
+        println!("  symlink: {}", pattern);
+    }
+    for dir in project.seed.dirs.iter().flatten() {
+        println!("  seed: {}", dir);
+    }
+    println!("  Worktrees with the same {} use these settings from now on", PROJECT_CONFIG_FILE);
+    Ok(())
+}
+
+pub fn handle_untrust(repo_root: &Path) -> Result<()> {
+    db::set_project_config_approved(repo_root, None)?;
+    println!("✓ Hooks, commands and file settings of {} are ignored again", PROJECT_CONFIG_FILE);
+    Ok(())
+}
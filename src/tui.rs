@@ -1,6 +1,6 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -48,7 +48,7 @@ pub fn show_project_selector() -> Result<()> {
                 })
                 .collect();
             
-            matches.sort_by(|a, b| b.1.cmp(&a.1));
+            matches.sort_by_key(|m| std::cmp::Reverse(m.1));
 
             if let Some((idx, _)) = matches.first() {
                 let project = &projects[*idx];
@@ -56,11 +56,11 @@ pub fn show_project_selector() -> Result<()> {
                 println!("\n{} {}", messages.switching_to_project(), project.name);
                 println!("  cd {}", project.path.display());
                 
-                crate::setup::SetupManager::run_auto_setup(&project.path)?;
+                worktree::prepare_worktree(&project.path)?;
             }
         }
-        SelectorAction::Delete(_) | SelectorAction::Cancel => {
-            // Do nothing for delete (not supported for projects) or cancel
+        SelectorAction::Delete(_) | SelectorAction::Lock(_) | SelectorAction::Open(_) | SelectorAction::Cancel => {
+            // Do nothing for delete/lock/open (not supported for projects) or cancel
         }
     }
 
@@ -74,8 +74,20 @@ pub fn show_worktree_selector(repo_root: &Path) -> Result<()> {
     let items: Vec<String> = worktrees
         .iter()
         .map(|wt| {
-            let marker = if wt.is_main { " (main)" } else { "" };
-            wt.branch.clone() + marker
+            let marker = if wt.is_main {
+                " (main)".to_string()
+            } else if let Some(reason) = &wt.locked {
+                if reason.is_empty() {
+                    " (🔒)".to_string()
+                } else {
+                    format!(" (🔒 {})", reason)
+                }
+            } else if wt.detached {
+                " (detached)".to_string()
+            } else {
+                String::new()
+            };
+            format!("{}{}", wt.name(), marker)
         })
         .collect();
 
@@ -95,52 +107,88 @@ pub fn show_worktree_selector(repo_root: &Path) -> Result<()> {
             };
 
             if force_create {
-                // Explicitly create new worktree
+                // Explicitly create new worktree, letting the user pick the base ref
+                let base = match select_base_ref(repo_root, &messages)? {
+                    Some(base) => base,
+                    None => return Ok(()),
+                };
                 println!("\n{} {}", messages.creating_new_worktree(), branch_name);
-                worktree::handle_worktree(repo_root, &branch_name)?;
+                worktree::handle_worktree(repo_root, &branch_name, Some(&base))?;
             } else {
                 // Check for exact match (case-insensitive)
                 let exact_match = worktrees.iter().find(|wt| 
-                    wt.branch.eq_ignore_ascii_case(&branch_name)
+                    wt.name().eq_ignore_ascii_case(&branch_name)
                 );
 
                 if let Some(wt) = exact_match {
                     // Existing worktree - switch to it
-                    println!("\n{} {}", messages.switching_to_worktree(), wt.branch);
+                    println!("\n{} {}", messages.switching_to_worktree(), wt.name());
                     println!("  cd {}", wt.path.display());
                     
-                    crate::setup::SetupManager::run_auto_setup(&wt.path)?;
+                    worktree::prepare_worktree(&wt.path)?;
                 } else {
                     // No exact match - this shouldn't happen with new logic
                     println!("\n{} {}", messages.creating_new_worktree(), branch_name);
-                    worktree::handle_worktree(repo_root, &branch_name)?;
+                    worktree::handle_worktree(repo_root, &branch_name, None)?;
                 }
             }
         }
         SelectorAction::Delete(branch_name) => {
             // Find the worktree to delete
             let worktree_to_delete = worktrees.iter().find(|wt| 
-                wt.branch.eq_ignore_ascii_case(&branch_name)
+                wt.name().eq_ignore_ascii_case(&branch_name)
             );
 
             if let Some(wt) = worktree_to_delete {
                 if wt.is_main {
                     eprintln!("{}", messages.cannot_delete_main());
+                } else if !confirm_unpushed(repo_root, wt, &messages)? {
+                    eprintln!("{}", messages.deletion_cancelled());
+                } else if !confirm_locked_override(wt, &messages)? {
+                    eprintln!("{}{}", messages.worktree_is_locked(), wt.locked.as_deref().map(worktree::format_lock_reason).unwrap_or_default());
                 } else {
-                    println!("\n{} {}", messages.deleting_worktree(), wt.branch);
-                    match git::remove_worktree(repo_root, &wt.path) {
+                    println!("\n{} {}", messages.deleting_worktree(), wt.name());
+                    let use_trash = crate::config::load_config()?.trash.enabled;
+                    match worktree::remove(repo_root, wt, use_trash, false) {
                         Ok(_) => {
-                            println!("{}", messages.worktree_deleted().replace("{}", &wt.branch));
+                            println!("{}", messages.worktree_deleted().replace("{}", wt.name()));
+                            if !wt.detached {
+                                offer_branch_deletion(repo_root, &wt.branch, &messages)?;
+                            }
                         }
                         Err(e) => {
                             eprintln!("\n{} {}", messages.failed_to_delete(), e);
                             eprintln!("\n{}", messages.uncommitted_changes_tip());
+                            eprintln!("{} wt rm {} --stash", messages.stash_delete_command(), wt.name());
                             eprintln!("{} {}", messages.force_delete_command(), wt.path.display());
                         }
                     }
                 }
             }
         }
+        SelectorAction::Lock(branch_name) => {
+            let worktree_to_lock = worktrees.iter().find(|wt| 
+                wt.name().eq_ignore_ascii_case(&branch_name)
+            );
+
+            if let Some(wt) = worktree_to_lock {
+                if wt.locked.is_some() {
+                    worktree::handle_unlock(repo_root, wt.name())?;
+                } else if let Some(reason) = run_text_prompt(messages.enter_lock_reason(), &messages)? {
+                    let reason = reason.trim();
+                    worktree::handle_lock(repo_root, wt.name(), (!reason.is_empty()).then_some(reason))?;
+                }
+            }
+        }
+        SelectorAction::Open(branch_name) => {
+            let worktree_to_open = worktrees.iter().find(|wt| 
+                wt.name().eq_ignore_ascii_case(&branch_name)
+            );
+
+            if let Some(wt) = worktree_to_open {
+                crate::open::open_worktree(repo_root, wt, None, false)?;
+            }
+        }
         SelectorAction::Cancel => {
             // Do nothing
         }
@@ -149,19 +197,243 @@ pub fn show_worktree_selector(repo_root: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Ask whether to delete the branch of a removed worktree.
+/// Enter follows `remove.delete_branch` from config.
+fn offer_branch_deletion(repo_root: &Path, branch: &str, messages: &crate::i18n::Messages) -> Result<()> {
+    let default = if crate::config::load_config()?.remove.delete_branch { 'y' } else { 'n' };
+    let title = messages.confirm_delete_branch().replace("{}", branch);
+    let lines = vec![messages.delete_branch_explanation().to_string()];
+
+    let force = match run_choice(&title, &lines, &['y', 'f', 'n'], Some(default), messages.help_delete_branch())? {
+        Some('y') => false,
+        Some('f') => true,
+        _ => return Ok(()),
+    };
+
+    let outcome = worktree::delete_branch(repo_root, branch, force)?;
+    worktree::report_branch_outcome(branch, &outcome);
+    Ok(())
+}
+
+/// Worktrees with commits that exist nowhere else are only deleted after confirmation.
+/// Returns true if deletion may proceed.
+fn confirm_unpushed(repo_root: &Path, wt: &git::WorktreeInfo, messages: &crate::i18n::Messages) -> Result<bool> {
+    let unpushed = worktree::unpushed_commits(repo_root, wt)?;
+    if unpushed.is_empty() {
+        return Ok(true);
+    }
+
+    let mut lines = vec![
+        messages.unpushed_commits().replace("{}", &unpushed.len().to_string()),
+        String::new(),
+    ];
+    lines.extend(unpushed.iter().map(|c| format!("  {}", c)));
+
+    run_confirm(messages.confirm_delete_unpushed(), &lines, messages)
+}
+
+/// Locked worktrees are only deleted after explicit confirmation; confirming unlocks them.
+/// Returns true if the worktree is unlocked and may be deleted.
+fn confirm_locked_override(wt: &git::WorktreeInfo, messages: &crate::i18n::Messages) -> Result<bool> {
+    let Some(reason) = &wt.locked else {
+        return Ok(true);
+    };
+
+    let mut lines = vec![format!("{} {}", messages.worktree_is_locked(), wt.name())];
+    if !reason.is_empty() {
+        lines.push(format!("{} {}", messages.lock_reason(), reason));
+    }
+
+    // worktree::remove unlocks right before removing, so a failed removal keeps the lock
+    run_confirm(messages.confirm_delete_locked(), &lines, messages)
+}
+
+/// Let the user pick the ref a new branch starts from.
+/// The default (configured `default_base`, else the main repo's branch) is listed first.
+/// Returns None if the user cancels.
+fn select_base_ref(repo_root: &Path, messages: &crate::i18n::Messages) -> Result<Option<String>> {
+    let default = match worktree::resolve_base(repo_root, None)? {
+        Some(base) => Some(base),
+        None => git::current_branch(repo_root)?,
+    };
+
+    let mut refs = git::list_refs(repo_root)?;
+    if let Some(default) = &default {
+        refs.retain(|r| r != default);
+        refs.insert(0, default.clone());
+    }
+
+    if refs.is_empty() {
+        return Ok(None);
+    }
+
+    match run_input_selector(messages.select_base_ref(), &refs, false, false, messages)? {
+        SelectorAction::Select(base) => Ok(Some(base)),
+        SelectorAction::Delete(_) | SelectorAction::Lock(_) | SelectorAction::Open(_) | SelectorAction::Cancel => Ok(None),
+    }
+}
+
 #[derive(Debug)]
 enum SelectorAction {
     Select(String),
     Delete(String),
+    Lock(String),
+    Open(String),
     Cancel,
 }
 
-fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_delete: bool, messages: &crate::i18n::Messages) -> Result<SelectorAction> {
+/// Find the item whose name (without markers like " (main)") exactly matches the input
+fn exact_match_name(items: &[String], input: &str) -> Option<String> {
+    items
+        .iter()
+        .map(|item| item.split(" (").next().unwrap_or(item))
+        .find(|name| name.eq_ignore_ascii_case(input))
+        .map(|name| name.to_string())
+}
+
+/// Apply a typed character or Backspace to the selector query. Ctrl shortcuts that
+/// don't apply (empty query, not allowed in this selector) leave it unchanged.
+fn edit_query(input: &mut String, key: &KeyEvent) {
+    match key.code {
+        KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => {}
+        KeyCode::Char(c) => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        _ => {}
+    }
+}
+
+fn enter_tui() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    Ok(Terminal::new(backend)?)
+}
+
+fn leave_tui(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Ask for a line of free text. Returns None if the user cancels.
+fn run_text_prompt(title: &str, messages: &crate::i18n::Messages) -> Result<Option<String>> {
+    let mut terminal = enter_tui()?;
+    let mut input = String::new();
+
+    let result = loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(2)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(3),
+                ])
+                .split(f.area());
+
+            let title_text = Paragraph::new(title)
+                .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Cyan)))
+                .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+            f.render_widget(title_text, chunks[0]);
+
+            let input_text = Paragraph::new(input.as_str())
+                .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Yellow)))
+                .style(Style::default().fg(Color::White));
+            f.render_widget(input_text, chunks[1]);
+
+            let help = Paragraph::new(format!("{} | {}", messages.help_enter_confirm(), messages.help_cancel()))
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(Color::Gray))
+                .alignment(Alignment::Center);
+            f.render_widget(help, chunks[3]);
+        })?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break None,
+                    KeyCode::Esc => break None,
+                    KeyCode::Enter => break Some(input),
+                    KeyCode::Char(c) => input.push(c),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    };
+
+    leave_tui(&mut terminal)?;
+    Ok(result)
+}
+
+/// Show a yes/no question with explanatory lines. Anything but 'y' answers no.
+fn run_confirm(title: &str, lines: &[String], messages: &crate::i18n::Messages) -> Result<bool> {
+    let choice = run_choice(title, lines, &['y', 'n'], None, messages.help_confirm())?;
+    Ok(choice == Some('y'))
+}
+
+/// Show a question with explanatory lines and wait for one of `keys`.
+/// Enter picks `default`; Esc and Ctrl+C return None.
+fn run_choice(title: &str, lines: &[String], keys: &[char], default: Option<char>, help: &str) -> Result<Option<char>> {
+    let mut terminal = enter_tui()?;
+
+    let result = loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(2)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(3),
+                ])
+                .split(f.area());
+
+            let title_text = Paragraph::new(title)
+                .block(Block::default().borders(Borders::ALL).style(Style::default().fg(Color::Red)))
+                .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+            f.render_widget(title_text, chunks[0]);
+
+            let body: Vec<Line> = lines.iter().map(|l| Line::from(l.as_str())).collect();
+            let body = Paragraph::new(body)
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(Color::White));
+            f.render_widget(body, chunks[1]);
+
+            let help = Paragraph::new(help)
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(Color::Gray))
+                .alignment(Alignment::Center);
+            f.render_widget(help, chunks[2]);
+        })?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break None,
+                    KeyCode::Char(c) if keys.contains(&c.to_ascii_lowercase()) => break Some(c.to_ascii_lowercase()),
+                    KeyCode::Enter => break default,
+                    KeyCode::Esc => break None,
+                    _ => {}
+                }
+            }
+        }
+    };
+
+    leave_tui(&mut terminal)?;
+    Ok(result)
+}
+
+fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_delete: bool, messages: &crate::i18n::Messages) -> Result<SelectorAction> {
+    let mut terminal = enter_tui()?;
 
     let mut input = String::new();
     let matcher = SkimMatcherV2::default();
@@ -176,7 +448,7 @@ fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_d
                     matcher.fuzzy_match(item, &input).map(|score| (item.clone(), score))
                 })
                 .collect();
-            matches.sort_by(|a, b| b.1.cmp(&a.1));
+            matches.sort_by_key(|m| std::cmp::Reverse(m.1));
             matches
         };
 
@@ -237,10 +509,7 @@ fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_d
             // Help
             let help_text = if allow_create && allow_delete {
                 // Check if input exactly matches an item
-                let has_exact_match = items.iter().any(|item| {
-                    let item_name = item.split(" (").next().unwrap_or(item);
-                    item_name.eq_ignore_ascii_case(&input)
-                });
+                let has_exact_match = exact_match_name(items, &input).is_some();
 
                 if input.is_empty() {
                     format!("{} | {} | {} | {} | {} {} | {}", 
@@ -249,9 +518,9 @@ fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_d
                 } else if filtered_items.is_empty() {
                     format!("{} | {} | {}", messages.help_create_new_branch(), messages.help_backspace(), messages.help_cancel())
                 } else if has_exact_match {
-                    format!("{} | {} | {} | {} | {} | {}", 
+                    format!("{} | {} | {} | {} | {} | {} | {} | {}", 
                         messages.help_tab(), messages.help_enter_select(), messages.help_ctrl_b_create(), 
-                        messages.help_ctrl_x_delete(), messages.help_backspace(), messages.help_cancel())
+                        messages.help_ctrl_x_delete(), messages.help_ctrl_l_lock(), messages.help_ctrl_o_open(), messages.help_backspace(), messages.help_cancel())
                 } else {
                      format!("{} | {} | {} | {} | {}", 
                         messages.help_tab(), messages.help_enter_select(), messages.help_ctrl_b_create(), 
@@ -287,34 +556,29 @@ fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_d
                     KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         break SelectorAction::Cancel;
                     }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) && allow_create && !input.is_empty() => {
                         // Ctrl+B: Create new branch with current input (only if allowed)
-                        if allow_create && !input.is_empty() {
-                            break SelectorAction::Select(format!("__CREATE_NEW__{}", input));
-                        }
+                        break SelectorAction::Select(format!("__CREATE_NEW__{}", input));
                     }
-                    KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) && allow_delete && !input.is_empty() => {
                         // Ctrl+X: Delete exact match (only if allowed and input exactly matches)
-                        if allow_delete && !input.is_empty() {
-                            // Check for exact match
-                            let exact_match = items.iter().find(|item| {
-                                let item_name = item.split(" (").next().unwrap_or(item);
-                                item_name.eq_ignore_ascii_case(&input)
-                            });
-
-                            if let Some(matched) = exact_match {
-                                let branch = matched.split(" (").next().unwrap_or(matched).to_string();
-                                break SelectorAction::Delete(branch);
-                            }
+                        if let Some(branch) = exact_match_name(items, &input) {
+                            break SelectorAction::Delete(branch);
                         }
                     }
-                    KeyCode::Esc => break SelectorAction::Cancel,
-                    KeyCode::Char(c) => {
-                        input.push(c);
+                    KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) && allow_delete && !input.is_empty() => {
+                        // Ctrl+L: Toggle lock on exact match
+                        if let Some(branch) = exact_match_name(items, &input) {
+                            break SelectorAction::Lock(branch);
+                        }
                     }
-                    KeyCode::Backspace => {
-                        input.pop();
+                    KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) && allow_delete && !input.is_empty() => {
+                        // Ctrl+O: Open exact match in the editor
+                        if let Some(branch) = exact_match_name(items, &input) {
+                            break SelectorAction::Open(branch);
+                        }
                     }
+                    KeyCode::Esc => break SelectorAction::Cancel,
                     KeyCode::Tab => {
                         // Autocomplete with top match
                         if let Some((matched, _)) = filtered_items.first() {
@@ -331,15 +595,41 @@ fn run_input_selector(title: &str, items: &[String], allow_create: bool, allow_d
                             break SelectorAction::Select(branch);
                         }
                     }
-                    _ => {}
+                    _ => edit_query(&mut input, &key),
                 }
             }
         }
     };
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    leave_tui(&mut terminal)?;
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(input: &str, code: KeyCode, modifiers: KeyModifiers) -> String {
+        let mut input = input.to_string();
+        edit_query(&mut input, &KeyEvent::new(code, modifiers));
+        input
+    }
+
+    #[test]
+    fn ctrl_shortcuts_never_edit_the_query() {
+        // Ctrl+L is only handled for an exact match; otherwise it must not type an `l`
+        assert_eq!(typed("", KeyCode::Char('l'), KeyModifiers::CONTROL), "");
+        assert_eq!(typed("feat", KeyCode::Char('l'), KeyModifiers::CONTROL), "feat");
+        // Same for Ctrl+O, which opens only an exact match
+        assert_eq!(typed("", KeyCode::Char('o'), KeyModifiers::CONTROL), "");
+        assert_eq!(typed("feat", KeyCode::Char('o'), KeyModifiers::CONTROL), "feat");
+        for c in ['b', 'x'] {
+            assert_eq!(typed("", KeyCode::Char(c), KeyModifiers::CONTROL), "");
+        }
+
+        assert_eq!(typed("feat", KeyCode::Char('l'), KeyModifiers::NONE), "featl");
+        assert_eq!(typed("feat", KeyCode::Char('L'), KeyModifiers::SHIFT), "featL");
+        assert_eq!(typed("feat", KeyCode::Backspace, KeyModifiers::NONE), "fea");
+    }
+}
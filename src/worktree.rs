@@ -1,10 +1,13 @@
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
+use crate::config;
 use crate::db;
 use crate::git;
+use crate::hooks::{self, Hook};
 
 /// Get the hashed name for a project
 fn get_hashed_name(repo_path: &Path) -> String {
@@ -37,52 +40,584 @@ fn get_worktree_path(repo_path: &Path, branch: &str) -> Result<PathBuf> {
 
 
 /// Change to the worktree directory and run setup
-fn switch_to_worktree(worktree_path: &Path) -> Result<()> {
+pub fn switch_to_worktree(worktree_path: &Path) -> Result<()> {
     // We can't actually change the directory of the parent shell from Rust
     // Instead, we'll print the command for the user to execute
     println!("\n✓ Worktree ready at: {}", worktree_path.display());
     println!("\nTo switch to this worktree, run:");
     println!("  cd {}", worktree_path.display());
 
-    crate::setup::SetupManager::run_auto_setup(worktree_path)?;
-    
+    prepare_worktree(worktree_path)
+}
+
+/// Bootstrap a worktree the shell is switching to: automatic setup, then post_switch hooks.
+/// post_switch hooks expect a working setup and are skipped when it failed; tmux still
+/// opens, since the switch happens anyway, and the failure is returned afterwards.
+pub fn prepare_worktree(worktree_path: &Path) -> Result<()> {
+    let repo_root = git::find_main_repo_root(worktree_path)?
+        .unwrap_or_else(|| worktree_path.to_path_buf());
+    let project = config::load_project_config(&repo_root, worktree_path)?;
+
+    crate::ports::prepare(&repo_root, worktree_path, &project.ports)?;
+    let setup = crate::setup::SetupManager::run_auto_setup(worktree_path, &project);
+
+    // Detached worktrees are named after their commit
+    let name = match git::list_worktrees(&repo_root)?.into_iter().find(|wt| wt.path == worktree_path) {
+        Some(wt) => wt.name().to_string(),
+        None => git::current_branch(worktree_path)?.unwrap_or_default(),
+    };
+    if setup.is_ok() {
+        hooks::run_hook_or_warn(Hook::PostSwitch, &project, &repo_root, worktree_path, &name);
+    }
+
+    if let Err(e) = crate::tmux::open(&repo_root, worktree_path, &name, &project.tmux) {
+        eprintln!("Warning: Could not open tmux session: {}", e);
+    }
+
+    setup.map(|_| ())
+}
+
+/// Run post_create hooks for a freshly added worktree
+pub fn run_post_create(repo_root: &Path, worktree_path: &Path, name: &str) -> Result<()> {
+    let project = config::load_project_config(repo_root, worktree_path)?;
+    hooks::run_hook_or_warn(Hook::PostCreate, &project, repo_root, worktree_path, name);
     Ok(())
 }
 
+/// Bring local files and seeded dependency directories into a freshly added worktree,
+/// then run post_create hooks
+fn finish_creation(repo_root: &Path, worktree_path: &Path, name: &str) -> Result<()> {
+    let project = config::load_project_config(repo_root, worktree_path)?;
+    crate::local_files::bring_local_files(repo_root, worktree_path, &project.files)?;
+    crate::seed::seed_worktree(repo_root, worktree_path, &project.seed)?;
+    crate::ports::prepare(repo_root, worktree_path, &project.ports)?;
+    hooks::run_hook_or_warn(Hook::PostCreate, &project, repo_root, worktree_path, name);
+    Ok(())
+}
+
+/// Resolve the ref a new branch should start from.
+/// An explicit `--from` must exist; the configured default is used only if it resolves.
+pub fn resolve_base(repo_root: &Path, base: Option<&str>) -> Result<Option<String>> {
+    if let Some(base) = base {
+        if !git::ref_exists(repo_root, base) {
+            anyhow::bail!("Base ref '{}' does not exist", base);
+        }
+        return Ok(Some(base.to_string()));
+    }
+
+    let config = config::load_config()?;
+    Ok(config
+        .default_base
+        .filter(|default| git::ref_exists(repo_root, default)))
+}
+
 /// Handle worktree creation or switching
-pub fn handle_worktree(repo_root: &Path, branch: &str) -> Result<()> {
+pub fn handle_worktree(repo_root: &Path, branch: &str, base: Option<&str>) -> Result<()> {
+    let worktree_path = create_worktree(repo_root, branch, base)?;
+    switch_to_worktree(&worktree_path)
+}
+
+/// Create worktrees for several branches, then set them all up in parallel
+pub fn handle_batch(repo_root: &Path, entries: &[(String, Option<String>)], jobs: usize) -> Result<()> {
+    let mut setup_jobs = Vec::new();
+    let mut failed = 0;
+
+    for (branch, base) in entries {
+        match create_worktree(repo_root, branch, base.as_deref()) {
+            Ok(path) => setup_jobs.push(crate::setup::SetupJob { name: branch.clone(), path }),
+            Err(e) => {
+                eprintln!("✗ Failed to create worktree '{}': {:#}", branch, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("\n✓ {} worktree(s) ready, running setup ({} at a time)", setup_jobs.len(), jobs);
+    crate::setup::SetupManager::run_jobs(&setup_jobs, jobs)?;
+
+    if failed > 0 {
+        anyhow::bail!("Failed to create {} of {} worktree(s)", failed, entries.len());
+    }
+    Ok(())
+}
+
+/// Read `branch [base]` lines of a batch file; blank lines and `#` comments are skipped.
+/// A comment starts at a word beginning with `#`, so branch names may contain `#`.
+pub fn read_batch_file(path: &Path) -> Result<Vec<(String, Option<String>)>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace().take_while(|word| !word.starts_with('#'));
+            let branch = words.next()?;
+            Some((branch.to_string(), words.next().map(|b| b.to_string())))
+        })
+        .collect())
+}
+
+/// Create (or find) the worktree of a branch, without switching to it
+fn create_worktree(repo_root: &Path, branch: &str, base: Option<&str>) -> Result<PathBuf> {
     let worktree_path = get_worktree_path(repo_root, branch)?;
 
     // Check if worktree already exists
     if worktree_path.exists() {
         println!("Worktree already exists for branch '{}'", branch);
+        if base.is_some() {
+            println!("Ignoring --from: branch '{}' already has a worktree", branch);
+        }
         db::update_last_accessed(repo_root)?;
-        return switch_to_worktree(&worktree_path);
+        return Ok(worktree_path);
     }
 
+    let explicit_base = base;
+    let base = resolve_base(repo_root, base)?;
+
     // Create worktree base directory
     let wt_base = get_worktree_base(repo_root)?;
     fs::create_dir_all(&wt_base)?;
 
     // Try to add worktree for existing branch first
     println!("Adding worktree for branch '{}'", branch);
-    let result = git::add_worktree(repo_root, &worktree_path, branch, false);
+    let result = git::add_worktree(repo_root, &worktree_path, branch, git::AddMode::Existing);
 
-    match result {
+    let created_from = match result {
         Ok(_) => {
             println!("✓ Worktree added for existing branch '{}'", branch);
+            if let Some(base) = explicit_base {
+                eprintln!("Warning: branch '{}' already exists, so it was not created from '{}'", branch, base);
+            }
+            None
         }
         Err(_) => {
             // Branch doesn't exist, create new one
-            println!("Branch '{}' not found, creating new branch", branch);
-            git::add_worktree(repo_root, &worktree_path, branch, true)
+            let base_ref = base.as_deref();
+            match base_ref {
+                Some(base_ref) => println!("Branch '{}' not found, creating new branch from '{}'", branch, base_ref),
+                None => println!("Branch '{}' not found, creating new branch", branch),
+            }
+            git::add_worktree(repo_root, &worktree_path, branch, git::AddMode::NewBranch { base: base_ref })
                 .context("Failed to create new branch and worktree")?;
             println!("✓ Created new branch '{}' with worktree", branch);
+
+            // Without an explicit base the branch starts from the main repo's HEAD
+            match base {
+                Some(base) => Some(base),
+                None => git::current_branch(repo_root)?,
+            }
+        }
+    };
+
+    db::record_worktree(repo_root, branch, &worktree_path, created_from.as_deref())?;
+    db::update_last_accessed(repo_root)?;
+    offer_stash_restore(repo_root, branch, &worktree_path)?;
+    finish_creation(repo_root, &worktree_path, branch)?;
+
+    Ok(worktree_path)
+}
+
+/// Message prefix identifying stashes made by `wt rm --stash` for a worktree
+fn stash_prefix(name: &str) -> String {
+    format!("wt: {} @ ", name)
+}
+
+/// Offer to re-apply the stash saved when this branch's worktree was removed
+fn offer_stash_restore(repo_root: &Path, branch: &str, worktree_path: &Path) -> Result<()> {
+    let prefix = stash_prefix(branch);
+    // Subjects look like "On <branch>: wt: <branch> @ <path>"
+    let Some((stash, subject)) = git::stash_list(repo_root)?
+        .into_iter()
+        .find(|(_, subject)| subject.contains(&prefix))
+    else {
+        return Ok(());
+    };
+
+    println!("Found changes stashed when this worktree was removed: {} ({})", stash, subject);
+    if !std::io::stdin().is_terminal() {
+        println!("  To apply them, run: git -C {} stash pop {}", worktree_path.display(), stash);
+        return Ok(());
+    }
+
+    if crate::prompt::confirm("Re-apply the stashed changes?")? {
+        match git::stash_pop(worktree_path, &stash) {
+            Ok(_) => println!("✓ Re-applied stashed changes"),
+            Err(e) => eprintln!("✗ {}\nThe stash was kept; resolve conflicts and run: git stash drop {}", e, stash),
         }
     }
 
+    Ok(())
+}
+
+/// Create or switch to a detached worktree for a commit or tag
+pub fn handle_detached(repo_root: &Path, commit: &str) -> Result<()> {
+    let (full, short) = git::resolve_commit(repo_root, commit)?;
+    let name = format!("_detached/{}", short);
+    let worktree_path = get_worktree_path(repo_root, &name)?;
+
+    if worktree_path.exists() {
+        println!("Worktree already exists for commit '{}'", short);
+        db::update_last_accessed(repo_root)?;
+        return switch_to_worktree(&worktree_path);
+    }
+
+    fs::create_dir_all(get_worktree_base(repo_root)?.join("_detached"))?;
+
+    println!("Adding detached worktree for '{}' ({})", commit, short);
+    git::add_worktree(repo_root, &worktree_path, &full, git::AddMode::Detach)
+        .context("Failed to create detached worktree")?;
+    println!("✓ Detached worktree added at {}", short);
+
+    db::record_worktree(repo_root, &short, &worktree_path, Some(commit))?;
     db::update_last_accessed(repo_root)?;
+    finish_creation(repo_root, &worktree_path, &short)?;
     switch_to_worktree(&worktree_path)?;
 
     Ok(())
 }
+
+/// Fetch a pull/merge request into a local branch and create or switch to its worktree
+pub fn handle_pr(repo_root: &Path, number: u32, remote: Option<&str>) -> Result<()> {
+    let config = config::load_config()?.pr;
+    let remote = remote.unwrap_or(&config.remote);
+
+    let refspec = match config.refspec {
+        Some(refspec) => refspec,
+        None => {
+            let url = git::remote_url(repo_root, remote)?;
+            if url.contains("gitlab") {
+                config::GITLAB_MR_REFSPEC.to_string()
+            } else {
+                config::GITHUB_PR_REFSPEC.to_string()
+            }
+        }
+    };
+    let src = refspec.replace("{number}", &number.to_string());
+    let branch = config.branch.replace("{number}", &number.to_string());
+
+    // A branch checked out in a worktree can't be updated by fetch
+    let worktrees = git::list_worktrees(repo_root)?;
+    if let Some(wt) = worktrees.iter().find(|wt| wt.branch == branch) {
+        println!("Branch '{}' is already checked out", branch);
+        println!("To update it, run: git -C {} pull {} {}", wt.path.display(), remote, src);
+        db::update_last_accessed(repo_root)?;
+        return switch_to_worktree(&wt.path);
+    }
+
+    println!("Fetching {} from '{}' into '{}'", src, remote, branch);
+    let local_ref = format!("refs/heads/{}", branch);
+    let previous = git::rev_parse(repo_root, &local_ref);
+    git::fetch_ref(repo_root, remote, &src, &local_ref)?;
+
+    // The request may have been force-pushed; keep the old commits findable
+    if let Some(previous) = previous {
+        if !git::is_ancestor(repo_root, &previous, &local_ref) {
+            println!(
+                "Branch '{}' was reset to the fetched request; its previous commit was {}",
+                branch,
+                &previous[..previous.len().min(12)]
+            );
+        }
+    }
+
+    handle_worktree(repo_root, &branch, None)
+}
+
+/// Find a worktree by branch name (or short commit for detached worktrees)
+pub fn find_worktree(repo_root: &Path, name: &str) -> Result<git::WorktreeInfo> {
+    git::list_worktrees(repo_root)?
+        .into_iter()
+        .find(|wt| wt.name() == name)
+        .with_context(|| format!("No worktree found for '{}'", name))
+}
+
+/// Rename a worktree's branch and move it to the matching path
+pub fn handle_move(repo_root: &Path, old: &str, new: &str) -> Result<()> {
+    let wt = find_worktree(repo_root, old)?;
+
+    if wt.is_main {
+        anyhow::bail!("Cannot move the main worktree");
+    }
+    if let Some(reason) = &wt.locked {
+        anyhow::bail!("Worktree '{}' is locked{}", old, format_lock_reason(reason));
+    }
+    if wt.detached {
+        anyhow::bail!("Cannot rename detached worktree '{}': it has no branch", old);
+    }
+
+    let new_path = get_worktree_path(repo_root, new)?;
+    if new_path.exists() {
+        anyhow::bail!("Path already exists: {}", new_path.display());
+    }
+    if git::ref_exists(repo_root, &format!("refs/heads/{}", new)) {
+        anyhow::bail!("Branch '{}' already exists", new);
+    }
+
+    // Remember where the shell is before the directory moves away
+    let current_dir = std::env::current_dir().ok();
+
+    git::rename_branch(repo_root, old, new)?;
+    println!("✓ Renamed branch '{}' to '{}'", old, new);
+
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if let Err(e) = git::move_worktree(repo_root, &wt.path, &new_path) {
+        // Keep branch and worktree consistent
+        git::rename_branch(repo_root, new, old)?;
+        return Err(e);
+    }
+    println!("✓ Moved worktree to {}", new_path.display());
+
+    db::rename_worktree(repo_root, old, new, &wt.path, &new_path)?;
+
+    let project = config::load_project_config(repo_root, &new_path)?;
+    crate::tmux::rename(repo_root, old, new, &project.tmux);
+
+    if let Some(relative) = current_dir
+        .as_deref()
+        .and_then(|dir| dir.strip_prefix(&wt.path).ok())
+    {
+        println!("\nTo switch to the moved worktree, run:");
+        println!("  cd {}", new_path.join(relative).display());
+    }
+
+    Ok(())
+}
+
+/// Lock a worktree to protect it from removal and pruning
+pub fn handle_lock(repo_root: &Path, name: &str, reason: Option<&str>) -> Result<()> {
+    let wt = find_worktree(repo_root, name)?;
+    if wt.is_main {
+        anyhow::bail!("The main worktree cannot be locked");
+    }
+    if let Some(existing) = &wt.locked {
+        anyhow::bail!("Worktree '{}' is already locked{}", name, format_lock_reason(existing));
+    }
+
+    git::lock_worktree(repo_root, &wt.path, reason)?;
+    println!("🔒 Locked worktree '{}'", name);
+    Ok(())
+}
+
+/// Unlock a previously locked worktree
+pub fn handle_unlock(repo_root: &Path, name: &str) -> Result<()> {
+    let wt = find_worktree(repo_root, name)?;
+    if wt.locked.is_none() {
+        anyhow::bail!("Worktree '{}' is not locked", name);
+    }
+
+    git::unlock_worktree(repo_root, &wt.path)?;
+    println!("✓ Unlocked worktree '{}'", name);
+    Ok(())
+}
+
+/// Format a lock reason for display, e.g. ": release freeze"
+pub fn format_lock_reason(reason: &str) -> String {
+    if reason.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", reason)
+    }
+}
+
+/// What happened to a branch after its worktree was removed
+pub enum BranchOutcome {
+    Deleted,
+    Kept(String),
+}
+
+/// Delete a branch whose worktree was removed.
+/// Without `force` the branch is only deleted if it is merged into the default branch or pushed.
+pub fn delete_branch(repo_root: &Path, branch: &str, force: bool) -> Result<BranchOutcome> {
+    if !force {
+        let default_branch = git::default_branch(repo_root)?;
+        let merged = git::is_ancestor(repo_root, branch, &default_branch);
+        let pushed = git::is_pushed(repo_root, branch);
+
+        if !merged && !pushed {
+            return Ok(BranchOutcome::Kept(format!(
+                "not merged into '{}' and not pushed to any remote",
+                default_branch
+            )));
+        }
+    }
+
+    git::delete_branch(repo_root, branch)?;
+    Ok(BranchOutcome::Deleted)
+}
+
+/// Print the result of `delete_branch`
+pub fn report_branch_outcome(branch: &str, outcome: &BranchOutcome) {
+    match outcome {
+        BranchOutcome::Deleted => println!("✓ Deleted branch '{}'", branch),
+        BranchOutcome::Kept(reason) => {
+            println!("Kept branch '{}': {}", branch, reason);
+            println!("  To delete it anyway, run: git branch -D {}", branch);
+        }
+    }
+}
+
+/// Commits of a worktree that exist nowhere else: not on a remote and not merged into
+/// the default branch (for detached worktrees: not on any branch or tag either)
+pub fn unpushed_commits(repo_root: &Path, wt: &git::WorktreeInfo) -> Result<Vec<String>> {
+    if wt.detached {
+        git::unpushed_commits(repo_root, &wt.head, &["--branches", "--tags"])
+    } else {
+        let default_branch = git::default_branch(repo_root)?;
+        git::unpushed_commits(repo_root, &wt.head, &[&default_branch])
+    }
+}
+
+/// Remove a worktree, moving it to the trash when `use_trash` is set. With `stash`,
+/// uncommitted changes are stashed once the pre_remove hook passed, and put back if
+/// the worktree can't be removed.
+pub fn remove(repo_root: &Path, wt: &git::WorktreeInfo, use_trash: bool, stash: bool) -> Result<()> {
+    let project = config::load_project_config(repo_root, &wt.path)?;
+    hooks::run_hook(Hook::PreRemove, &project, repo_root, &wt.path, wt.name())?;
+
+    let stashed = if stash {
+        let message = format!("{}{}", stash_prefix(wt.name()), wt.path.display());
+        let stashed = git::stash_push(&wt.path, &message)?;
+        if stashed {
+            println!("✓ Stashed changes as \"{}\"", message);
+        }
+        stashed.then_some(message)
+    } else {
+        None
+    };
+
+    // Callers only confirm removing a locked worktree; it stays locked until the last moment
+    if wt.locked.is_some() {
+        git::unlock_worktree(repo_root, &wt.path)?;
+    }
+
+    let result = if use_trash {
+        crate::trash::trash_worktree(repo_root, wt).map(|entry| {
+            println!("Moved to trash as '{}' ({} untracked file(s) saved)", entry.id, entry.files.len());
+            println!("  To restore it, run: wt undo");
+        })
+    } else {
+        git::remove_worktree(repo_root, &wt.path, false)
+    };
+
+    if let Err(e) = result {
+        if let Some(reason) = &wt.locked {
+            if let Err(lock_error) = git::lock_worktree(repo_root, &wt.path, (!reason.is_empty()).then_some(reason.as_str())) {
+                eprintln!("Warning: failed to lock the worktree again: {}", lock_error);
+            }
+        }
+        if let Some(message) = stashed {
+            match git::stash_pop(&wt.path, "stash@{0}") {
+                Ok(()) => println!("Put the stashed changes back into the worktree"),
+                Err(pop_error) => eprintln!("Warning: changes are still stashed as \"{}\": {}", message, pop_error),
+            }
+        }
+        return Err(e);
+    }
+
+    // post_remove hooks still see the worktree's ports
+    hooks::run_hook_or_warn(Hook::PostRemove, &project, repo_root, &wt.path, wt.name());
+    db::remove_worktree_record(repo_root, wt.name(), &wt.path)?;
+    crate::tmux::close(repo_root, wt.name(), &project.tmux);
+    Ok(())
+}
+
+pub struct RemoveOptions {
+    /// Delete the branch after removal; None uses `remove.delete_branch` from config
+    pub delete_branch: Option<bool>,
+    /// Delete the branch even if it is neither merged nor pushed
+    pub force_branch: bool,
+    /// Unlock a locked worktree instead of refusing to remove it
+    pub unlock: bool,
+    /// Remove the worktree even if it has commits that exist nowhere else
+    pub allow_unpushed: bool,
+    /// Move the worktree to the trash; None uses `trash.enabled` from config
+    pub trash: Option<bool>,
+    /// Stash uncommitted changes before removal so they can be re-applied later
+    pub stash: bool,
+}
+
+/// Remove a worktree from the command line
+pub fn handle_remove(repo_root: &Path, name: &str, options: &RemoveOptions) -> Result<()> {
+    let wt = find_worktree(repo_root, name)?;
+
+    if wt.is_main {
+        anyhow::bail!("Cannot delete the main worktree");
+    }
+    if let Some(reason) = &wt.locked {
+        if !options.unlock {
+            anyhow::bail!(
+                "Worktree '{}' is locked{} (use --unlock to remove it anyway)",
+                name,
+                format_lock_reason(reason)
+            );
+        }
+    }
+
+    let unpushed = unpushed_commits(repo_root, &wt)?;
+    if !unpushed.is_empty() {
+        eprintln!("Worktree '{}' has {} commit(s) not on any remote:", name, unpushed.len());
+        for commit in &unpushed {
+            eprintln!("  {}", commit);
+        }
+        if !options.allow_unpushed {
+            anyhow::bail!("Push these commits first, or use --allow-unpushed to remove the worktree anyway");
+        }
+    }
+
+    let config = config::load_config()?;
+    let use_trash = options.trash.unwrap_or(config.trash.enabled);
+
+    println!("Removing worktree '{}'", name);
+    if let Err(e) = remove(repo_root, &wt, use_trash, options.stash) {
+        if options.stash || !git::is_dirty(&wt.path).unwrap_or(false) {
+            return Err(e);
+        }
+        return Err(e.context(format!(
+            "The worktree has uncommitted changes. To keep them, run:\n  wt rm {} --stash\nTo force delete, run:\n  git worktree remove --force {}",
+            name,
+            wt.path.display()
+        )));
+    }
+    println!("✓ Worktree '{}' removed", name);
+
+    let delete = options.delete_branch.unwrap_or(config.remove.delete_branch);
+    if delete && !wt.detached {
+        let outcome = delete_branch(repo_root, &wt.branch, options.force_branch)?;
+        report_branch_outcome(&wt.branch, &outcome);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Sandbox;
+
+    #[test]
+    fn batch_file_skips_comments_but_keeps_hash_in_names() {
+        let sandbox = Sandbox::new("batch");
+        let path = sandbox.path("branches.txt");
+        fs::write(
+            &path,
+            "# branches for the release\n\
+             \n\
+             feature/login\n\
+             fix/#123-crash main   # from main\n\
+             \t  # indented comment\n\
+             hotfix develop extra\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_batch_file(&path).unwrap(),
+            vec![
+                ("feature/login".to_string(), None),
+                ("fix/#123-crash".to_string(), Some("main".to_string())),
+                ("hotfix".to_string(), Some("develop".to_string())),
+            ]
+        );
+    }
+}
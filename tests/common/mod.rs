@@ -0,0 +1,146 @@
+//! Helpers for tests that need throwaway directories and repositories. Integration
+//! tests use it as `common`; unit tests include the same file as `crate::testutil`.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Temporary directory removed on drop; also serves as $HOME for `wt`
+pub struct Sandbox {
+    pub root: PathBuf,
+}
+
+impl Sandbox {
+    pub fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "wt-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        Self { root: root.canonicalize().unwrap() }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    /// Run `wt` in `dir` with this sandbox as $HOME and its `bin` directory first on
+    /// PATH, for fake tools (integration tests only)
+    pub fn wt(&self, dir: &Path, args: &[&str]) -> Output {
+        let Some(binary) = option_env!("CARGO_BIN_EXE_wt") else {
+            panic!("the wt binary is only built for integration tests");
+        };
+        let path = format!("{}:{}", self.path("bin").display(), std::env::var("PATH").unwrap_or_default());
+        let output = Command::new(binary)
+            .args(args)
+            .current_dir(dir)
+            .env("HOME", &self.root)
+            .env("PATH", path)
+            .envs(git_env(&self.root))
+            .env_remove("TMUX")
+            .env_remove("VISUAL")
+            .output()
+            .unwrap();
+        eprintln!("wt {}:\n{}{}", args.join(" "), String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        output
+    }
+
+    /// Run git in `dir`, panicking if it fails, and return trimmed stdout
+    pub fn git(&self, dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .envs(git_env(&self.root))
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// Create a repository with one commit on `main`. The identity is also stored in the
+    /// repository, so git commands that wt code runs itself can commit too.
+    pub fn init_repo(&self, name: &str) -> PathBuf {
+        let repo = self.path(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        self.git(&repo, &["init", "-q", "-b", "main"]);
+        self.git(&repo, &["config", "user.name", "wt test"]);
+        self.git(&repo, &["config", "user.email", "wt@example.com"]);
+        self.git(&repo, &["config", "commit.gpgsign", "false"]);
+        self.commit(&repo, "README", "hello\n", "initial");
+        repo
+    }
+
+    /// Put an executable shell script called `name` on the PATH of `wt`
+    pub fn fake_tool(&self, name: &str, script: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = self.path("bin").join(name);
+        std::fs::create_dir_all(self.path("bin")).unwrap();
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    /// Write a file and commit it
+    pub fn commit(&self, dir: &Path, file: &str, content: &str, message: &str) -> String {
+        std::fs::write(dir.join(file), content).unwrap();
+        self.git(dir, &["add", file]);
+        self.git(dir, &["commit", "-q", "-m", message]);
+        self.git(dir, &["rev-parse", "HEAD"])
+    }
+
+    /// Like `commit`, but dated `days_ago` days in the past
+    pub fn commit_days_ago(&self, dir: &Path, file: &str, content: &str, message: &str, days_ago: u64) -> String {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let date = format!("@{} +0000", now - days_ago * 24 * 60 * 60);
+        std::fs::write(dir.join(file), content).unwrap();
+        self.git(dir, &["add", file]);
+        let status = Command::new("git")
+            .args(["commit", "-q", "-m", message])
+            .current_dir(dir)
+            .envs(git_env(&self.root))
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        self.git(dir, &["rev-parse", "HEAD"])
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Identity and isolation from the user's git config
+fn git_env(home: &Path) -> Vec<(&'static str, String)> {
+    vec![
+        ("HOME", home.to_string_lossy().to_string()),
+        ("GIT_CONFIG_NOSYSTEM", "1".to_string()),
+        ("GIT_AUTHOR_NAME", "wt test".to_string()),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com".to_string()),
+        ("GIT_COMMITTER_NAME", "wt test".to_string()),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com".to_string()),
+    ]
+}
+
+/// Directory from the `  cd <path>` line `wt` prints for the shell wrapper
+pub fn cd_target(output: &Output) -> PathBuf {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("  cd "))
+        .map(PathBuf::from)
+        .expect("wt printed no cd line")
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::Sandbox;
+use std::thread;
+
+/// Parallel wt processes update db.json without losing each other's changes
+#[test]
+fn parallel_updates_are_not_lost() {
+    let sandbox = Sandbox::new("db-lock");
+    let repos: Vec<_> = (0..8)
+        .map(|i| {
+            let repo = sandbox.init_repo(&format!("repo{}", i));
+            sandbox.commit(&repo, ".wt.toml", "[hooks]\npost_create = [\"true\"]\n", "config");
+            repo
+        })
+        .collect();
+
+    thread::scope(|scope| {
+        for repo in &repos {
+            let sandbox = &sandbox;
+            scope.spawn(move || assert!(sandbox.wt(repo, &["trust"]).status.success()));
+        }
+    });
+
+    let db: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(sandbox.path(".wt-manager/db.json")).unwrap()).unwrap();
+    for repo in &repos {
+        let project = &db["projects"][repo.to_str().unwrap()];
+        assert!(project["project_config_approved"].is_string(), "{} lost its approval", repo.display());
+    }
+}
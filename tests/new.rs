@@ -0,0 +1,19 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// `wt status` runs the subcommand, so a branch called `status` needs `wt new`
+#[test]
+fn creates_branch_named_like_a_subcommand() {
+    let sandbox = Sandbox::new("new");
+    let repo = sandbox.init_repo("repo");
+
+    let output = sandbox.wt(&repo, &["new", "status", "--no-setup"]);
+    assert!(output.status.success());
+    let worktree = cd_target(&output);
+    assert_eq!(sandbox.git(&worktree, &["branch", "--show-current"]), "status");
+
+    let output = sandbox.wt(&repo, &["status"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("  cd "));
+}
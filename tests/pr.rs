@@ -0,0 +1,36 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// `wt pr` works against any remote, including a local bare repository
+#[test]
+fn checks_out_pull_request_from_bare_remote() {
+    let sandbox = Sandbox::new("pr");
+    let repo = sandbox.init_repo("repo");
+    let origin = sandbox.path("origin.git");
+    sandbox.git(&sandbox.root, &["clone", "-q", "--bare", repo.to_str().unwrap(), origin.to_str().unwrap()]);
+    sandbox.git(&repo, &["remote", "add", "origin", origin.to_str().unwrap()]);
+
+    // A contributor's commit, published the way GitHub exposes pull requests
+    let contributor = sandbox.path("contributor");
+    sandbox.git(&sandbox.root, &["clone", "-q", origin.to_str().unwrap(), contributor.to_str().unwrap()]);
+    let first = sandbox.commit(&contributor, "feature", "v1\n", "feature v1");
+    sandbox.git(&contributor, &["push", "-q", "origin", "HEAD:refs/pull/1/head"]);
+
+    let output = sandbox.wt(&repo, &["pr", "1", "--no-setup"]);
+    assert!(output.status.success());
+    let worktree = cd_target(&output);
+    assert_eq!(sandbox.git(&worktree, &["rev-parse", "HEAD"]), first);
+    assert_eq!(sandbox.git(&worktree, &["branch", "--show-current"]), "pr/1");
+
+    // After a force-push, fetching again replaces the local branch that has no worktree anymore
+    sandbox.git(&repo, &["worktree", "remove", worktree.to_str().unwrap()]);
+    sandbox.git(&contributor, &["commit", "-q", "--amend", "-m", "feature v1 (rewritten)"]);
+    sandbox.git(&contributor, &["push", "-q", "--force", "origin", "HEAD:refs/pull/1/head"]);
+    let rewritten = sandbox.git(&contributor, &["rev-parse", "HEAD"]);
+
+    let output = sandbox.wt(&repo, &["pr", "1", "--no-setup"]);
+    assert!(output.status.success());
+    assert_eq!(cd_target(&output), worktree);
+    assert_eq!(sandbox.git(&worktree, &["rev-parse", "HEAD"]), rewritten);
+}
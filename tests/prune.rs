@@ -0,0 +1,43 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// A worktree created from an old commit, without commits of its own, is neither
+/// merged nor stale after the default branch moves on
+#[test]
+fn keeps_new_worktree_without_own_commits() {
+    let sandbox = Sandbox::new("prune-new");
+    let repo = sandbox.init_repo("repo");
+    sandbox.commit_days_ago(&repo, "CHANGELOG", "old\n", "old release", 100);
+
+    let output = sandbox.wt(&repo, &["new", "fresh", "--no-setup"]);
+    assert!(output.status.success());
+    let worktree = cd_target(&output);
+    sandbox.commit_days_ago(&repo, "CHANGELOG", "newer\n", "newer release", 60);
+
+    let output = sandbox.wt(&repo, &["prune", "--days", "30", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("fresh"));
+
+    let output = sandbox.wt(&repo, &["prune", "--days", "30", "--yes"]);
+    assert!(output.status.success());
+    assert!(worktree.exists());
+}
+
+/// Once the branch's own commits reach the default branch, the worktree is pruned
+#[test]
+fn prunes_worktree_whose_commits_were_merged() {
+    let sandbox = Sandbox::new("prune-merged");
+    let repo = sandbox.init_repo("repo");
+
+    let output = sandbox.wt(&repo, &["new", "done", "--no-setup"]);
+    assert!(output.status.success());
+    let worktree = cd_target(&output);
+    sandbox.commit(&worktree, "feature", "done\n", "feature");
+    sandbox.git(&repo, &["merge", "-q", "--ff-only", "done"]);
+
+    let output = sandbox.wt(&repo, &["prune", "--dry-run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().any(|line| line.starts_with("done") && line.contains("merged") && line.ends_with("remove")));
+}
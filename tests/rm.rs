@@ -0,0 +1,31 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// A failing pre_remove hook stops `wt rm --stash` before anything is stashed
+#[test]
+fn stash_waits_for_pre_remove_hook() {
+    let sandbox = Sandbox::new("rm-stash");
+    let repo = sandbox.init_repo("repo");
+    sandbox.commit(&repo, ".wt.toml", "[hooks]\npre_remove = [\"test ! -e keep\"]\n", "hooks");
+    assert!(sandbox.wt(&repo, &["trust"]).status.success());
+
+    let output = sandbox.wt(&repo, &["new", "feature", "--no-setup"]);
+    assert!(output.status.success());
+    let worktree = cd_target(&output);
+    std::fs::write(worktree.join("README"), "changed\n").unwrap();
+    std::fs::write(worktree.join("keep"), "").unwrap();
+
+    let output = sandbox.wt(&repo, &["rm", "feature", "--stash", "--allow-unpushed"]);
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("--stash"));
+    assert_eq!(std::fs::read_to_string(worktree.join("README")).unwrap(), "changed\n");
+    assert_eq!(sandbox.git(&repo, &["stash", "list"]), "");
+
+    // Once the hook passes, the changes are stashed and the worktree is removed
+    std::fs::remove_file(worktree.join("keep")).unwrap();
+    let output = sandbox.wt(&repo, &["rm", "feature", "--stash", "--allow-unpushed"]);
+    assert!(output.status.success());
+    assert!(!worktree.exists());
+    assert!(sandbox.git(&repo, &["stash", "list"]).contains("wt: feature @ "));
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// A failed setup exits with code 3 after switching, without running post_switch hooks;
+/// with `setup.best_effort` it only warns
+#[test]
+fn failed_setup_exits_with_code_3_unless_best_effort() {
+    let sandbox = Sandbox::new("setup-exit");
+    let repo = sandbox.init_repo("repo");
+    sandbox.commit(&repo, "package-lock.json", "{}\n", "lockfile");
+    sandbox.commit(&repo, ".wt.toml", "[hooks]\npost_switch = [\"touch switched\"]\n", "hooks");
+    assert!(sandbox.wt(&repo, &["trust"]).status.success());
+    sandbox.fake_tool("npm", "echo 'npm ERR! broken' >&2; exit 1");
+
+    let output = sandbox.wt(&repo, &["new", "broken"]);
+    assert_eq!(output.status.code(), Some(3));
+    let worktree = cd_target(&output);
+    assert!(!worktree.join("switched").exists());
+
+    std::fs::create_dir_all(sandbox.path(".wt-manager")).unwrap();
+    std::fs::write(sandbox.path(".wt-manager/config.toml"), "[setup]\nbest_effort = true\n").unwrap();
+
+    let output = sandbox.wt(&repo, &["new", "lenient"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: Setup failed"));
+    assert!(cd_target(&output).join("switched").exists());
+}
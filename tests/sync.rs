@@ -0,0 +1,44 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// `wt sync` rebases clean worktrees onto the fetched base, skips dirty ones and
+/// reports conflicts without leaving a rebase in progress
+#[test]
+fn syncs_worktrees_onto_fetched_base() {
+    let sandbox = Sandbox::new("sync");
+    let repo = sandbox.init_repo("repo");
+    let origin = sandbox.path("origin.git");
+    sandbox.git(&sandbox.root, &["clone", "-q", "--bare", repo.to_str().unwrap(), origin.to_str().unwrap()]);
+    sandbox.git(&repo, &["remote", "add", "origin", origin.to_str().unwrap()]);
+    sandbox.git(&repo, &["fetch", "-q", "origin"]);
+    sandbox.git(&repo, &["branch", "-q", "--set-upstream-to", "origin/main", "main"]);
+
+    let mut worktrees = Vec::new();
+    for branch in ["clean", "conflict", "dirty"] {
+        let output = sandbox.wt(&repo, &["new", branch, "--no-setup"]);
+        assert!(output.status.success());
+        worktrees.push(cd_target(&output));
+    }
+    let [clean, conflict, dirty] = &worktrees[..] else { unreachable!() };
+    let conflict_head = sandbox.commit(conflict, "CHANGELOG", "conflict\n", "local changelog");
+    std::fs::write(dirty.join("README"), "uncommitted\n").unwrap();
+
+    // Someone else pushes to main
+    let contributor = sandbox.path("contributor");
+    sandbox.git(&sandbox.root, &["clone", "-q", origin.to_str().unwrap(), contributor.to_str().unwrap()]);
+    let upstream = sandbox.commit(&contributor, "CHANGELOG", "upstream\n", "upstream changelog");
+    sandbox.git(&contributor, &["push", "-q", "origin", "main"]);
+
+    let output = sandbox.wt(&repo, &["sync"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ clean: rebased (1 new commit)"));
+    assert!(stdout.contains("✗ conflict: needs attention: conflicts in CHANGELOG"));
+    assert!(stdout.contains("- dirty: skipped: uncommitted changes"));
+
+    assert_eq!(sandbox.git(clean, &["rev-parse", "HEAD"]), upstream);
+    assert_eq!(sandbox.git(conflict, &["rev-parse", "HEAD"]), conflict_head);
+    assert_eq!(sandbox.git(conflict, &["status", "--porcelain"]), "");
+    assert_eq!(sandbox.git(dirty, &["status", "--porcelain"]), "M README");
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+use std::path::{Path, PathBuf};
+
+fn trash_entries(sandbox: &Sandbox) -> Vec<PathBuf> {
+    let dir = sandbox.path(".wt-manager/trash");
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir).unwrap().map(|e| e.unwrap().path()).collect();
+    entries.sort();
+    entries
+}
+
+fn trash_refs(sandbox: &Sandbox, repo: &Path) -> String {
+    sandbox.git(repo, &["for-each-ref", "--format=%(refname)", "refs/wt-trash/"])
+}
+
+/// `wt rm --trash` keeps untracked files and the commit; `wt undo` brings both back,
+/// recreating the branch from the trash ref if it was deleted meanwhile
+#[test]
+fn undo_restores_trashed_worktree_and_branch() {
+    let sandbox = Sandbox::new("trash-undo");
+    let repo = sandbox.init_repo("repo");
+
+    let output = sandbox.wt(&repo, &["new", "feature", "--no-setup"]);
+    assert!(output.status.success());
+    let worktree = cd_target(&output);
+    let head = sandbox.commit(&worktree, "feature", "work\n", "feature work");
+    std::fs::write(worktree.join("notes.txt"), "untracked\n").unwrap();
+
+    let output = sandbox.wt(&repo, &["rm", "feature", "--trash", "--allow-unpushed"]);
+    assert!(output.status.success());
+    assert!(!worktree.exists());
+    assert_eq!(trash_entries(&sandbox).len(), 1);
+    assert!(trash_refs(&sandbox, &repo).starts_with("refs/wt-trash/"));
+
+    // Only the trash ref keeps the commit now
+    sandbox.git(&repo, &["branch", "-D", "feature"]);
+
+    let output = sandbox.wt(&repo, &["undo"]);
+    assert!(output.status.success());
+    assert_eq!(sandbox.git(&worktree, &["rev-parse", "HEAD"]), head);
+    assert_eq!(sandbox.git(&worktree, &["branch", "--show-current"]), "feature");
+    assert_eq!(std::fs::read_to_string(worktree.join("notes.txt")).unwrap(), "untracked\n");
+    assert!(trash_entries(&sandbox).is_empty());
+    assert_eq!(trash_refs(&sandbox, &repo), "");
+}
+
+/// `wt trash purge` deletes entries older than `trash.retention_days` and their refs
+#[test]
+fn purge_deletes_only_expired_entries() {
+    let sandbox = Sandbox::new("trash-purge");
+    let repo = sandbox.init_repo("repo");
+
+    for branch in ["old", "recent"] {
+        let output = sandbox.wt(&repo, &["new", branch, "--no-setup"]);
+        assert!(output.status.success());
+        let output = sandbox.wt(&repo, &["rm", branch, "--trash", "--allow-unpushed"]);
+        assert!(output.status.success());
+    }
+
+    // Age the first entry past the default retention of 14 days
+    let entries = trash_entries(&sandbox);
+    let old = entries.iter().find(|e| e.to_string_lossy().ends_with("-old")).unwrap();
+    let meta_path = old.join("meta.json");
+    let mut meta: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&meta_path).unwrap()).unwrap();
+    meta["trashed_at"] = serde_json::json!(meta["trashed_at"].as_u64().unwrap() - 15 * 24 * 60 * 60);
+    std::fs::write(&meta_path, meta.to_string()).unwrap();
+
+    let output = sandbox.wt(&repo, &["trash", "purge"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Purged 1 trash entry"));
+
+    let entries = trash_entries(&sandbox);
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].to_string_lossy().ends_with("-recent"));
+    let refs = trash_refs(&sandbox, &repo);
+    assert_eq!(refs.lines().count(), 1);
+    assert!(refs.ends_with("-recent"));
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use common::{cd_target, Sandbox};
+
+/// Files listed in `[files]` of `.wt.toml` are brought into new worktrees only after `wt trust`
+#[test]
+fn files_wait_for_trust() {
+    let sandbox = Sandbox::new("trust-files");
+    let repo = sandbox.init_repo("repo");
+    sandbox.commit(&repo, ".wt.toml", "[files]\ncopy = [\".secret\"]\n", "config");
+    std::fs::write(repo.join(".secret"), "token\n").unwrap();
+
+    let output = sandbox.wt(&repo, &["new", "untrusted", "--no-setup"]);
+    assert!(output.status.success());
+    assert!(!cd_target(&output).join(".secret").exists());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[files]"));
+
+    assert!(sandbox.wt(&repo, &["trust"]).status.success());
+    let output = sandbox.wt(&repo, &["new", "trusted", "--no-setup"]);
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(cd_target(&output).join(".secret")).unwrap(), "token\n");
+}
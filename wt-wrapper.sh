@@ -19,6 +19,15 @@ wt() {
         return 1
     fi
     
+    # Commands that run user commands get the terminal directly: their output
+    # is not a wt message, so it is neither tee'd nor scanned for cd/tmux lines
+    case "$1" in
+        exec|each)
+            "$wt_bin" "$@"
+            return $?
+            ;;
+    esac
+
     # Create temporary file for output
     local tmp_output=$(mktemp)
     
@@ -43,6 +52,13 @@ wt() {
             echo "✓ Changed to: $(pwd)"
         fi
     fi
+
+    # Attach to the worktree's tmux session (only outside tmux; inside, wt switches itself)
+    local tmux_line=$(grep "^  tmux attach -t " "$tmp_output" | head -n1)
+
+    if [[ -n "$tmux_line" && -z "$TMUX" ]]; then
+        tmux attach -t "=${tmux_line#  tmux attach -t }"
+    fi
     
     rm -f "$tmp_output"
     return $exit_code